@@ -0,0 +1,317 @@
+use concordium_std::*;
+
+// Identity attributes that can be asked for in an identity statement. Values
+// are validated when they are deserialized, so a parameter carrying an invalid
+// date, country or code is rejected before any entrypoint logic runs.
+
+#[derive(Serialize, SchemaType, Clone, Debug, PartialEq, Eq)]
+pub struct Attributes {
+    pub first_name: Option<String>,
+    pub last_name: Option<String>,
+    pub sex: Option<Sex>,
+    pub date_of_birth: Option<Date>,
+    pub country_of_residence: Option<CountryCode>,
+    pub country_of_nationality: Option<CountryCode>,
+    pub id_document_type: Option<IdDocumentType>,
+    pub id_document_number: Option<String>,
+    pub id_document_issuer: Option<IdDocumentIssuer>,
+    pub id_valid_from: Option<Date>,
+    pub id_valid_to: Option<Date>,
+    pub national_id_number: Option<String>,
+    pub tax_id_number: Option<String>,
+}
+
+impl Attributes {
+    // Tags of the attributes that are set, in the order of the fields.
+    pub fn tags(&self) -> Vec<AttributeTag> {
+        let fields = [
+            (self.first_name.is_some(), AttributeTag::FirstName),
+            (self.last_name.is_some(), AttributeTag::LastName),
+            (self.sex.is_some(), AttributeTag::Sex),
+            (self.date_of_birth.is_some(), AttributeTag::DateOfBirth),
+            (
+                self.country_of_residence.is_some(),
+                AttributeTag::CountryOfResidence,
+            ),
+            (
+                self.country_of_nationality.is_some(),
+                AttributeTag::Nationality,
+            ),
+            (self.id_document_type.is_some(), AttributeTag::IdDocType),
+            (self.id_document_number.is_some(), AttributeTag::IdDocNo),
+            (self.id_document_issuer.is_some(), AttributeTag::IdDocIssuer),
+            (self.id_valid_from.is_some(), AttributeTag::IdDocIssuedAt),
+            (self.id_valid_to.is_some(), AttributeTag::IdDocExpiresAt),
+            (
+                self.national_id_number.is_some(),
+                AttributeTag::NationalIdNo,
+            ),
+            (self.tax_id_number.is_some(), AttributeTag::TaxIdNo),
+        ];
+        fields
+            .into_iter()
+            .filter(|(set, _)| *set)
+            .map(|(_, tag)| tag)
+            .collect()
+    }
+}
+
+// The attributes of a Concordium identity, as named in identity statements.
+#[derive(Serialize, SchemaType, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AttributeTag {
+    FirstName,
+    LastName,
+    Sex,
+    DateOfBirth,
+    CountryOfResidence,
+    Nationality,
+    IdDocType,
+    IdDocNo,
+    IdDocIssuer,
+    IdDocIssuedAt,
+    IdDocExpiresAt,
+    NationalIdNo,
+    TaxIdNo,
+}
+
+impl AttributeTag {
+    pub fn name(&self) -> &'static str {
+        match self {
+            AttributeTag::FirstName => "firstName",
+            AttributeTag::LastName => "lastName",
+            AttributeTag::Sex => "sex",
+            AttributeTag::DateOfBirth => "dob",
+            AttributeTag::CountryOfResidence => "countryOfResidence",
+            AttributeTag::Nationality => "nationality",
+            AttributeTag::IdDocType => "idDocType",
+            AttributeTag::IdDocNo => "idDocNo",
+            AttributeTag::IdDocIssuer => "idDocIssuer",
+            AttributeTag::IdDocIssuedAt => "idDocIssuedAt",
+            AttributeTag::IdDocExpiresAt => "idDocExpiresAt",
+            AttributeTag::NationalIdNo => "nationalIdNo",
+            AttributeTag::TaxIdNo => "taxIdNo",
+        }
+    }
+}
+
+// A calendar date. Dates order chronologically, so they can be compared
+// directly, e.g. for age or expiry checks.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Date {
+    year: u16,
+    month: u8,
+    day: u8,
+}
+
+impl Date {
+    pub fn new(year: u16, month: u8, day: u8) -> Result<Self, ParseError> {
+        ensure!((1..=9999).contains(&year), ParseError::default());
+        ensure!((1..=12).contains(&month), ParseError::default());
+        ensure!(
+            day >= 1 && day <= days_in_month(year, month),
+            ParseError::default()
+        );
+        Ok(Self { year, month, day })
+    }
+
+    pub fn year(&self) -> u16 {
+        self.year
+    }
+
+    pub fn month(&self) -> u8 {
+        self.month
+    }
+
+    pub fn day(&self) -> u8 {
+        self.day
+    }
+
+    // The `YYYYMMDD` encoding used for dates in identity statements.
+    pub fn to_attribute_string(&self) -> String {
+        format!("{:04}{:02}{:02}", self.year, self.month, self.day)
+    }
+
+    pub fn from_attribute_string(value: &str) -> Result<Self, ParseError> {
+        ensure!(
+            value.len() == 8 && value.bytes().all(|b| b.is_ascii_digit()),
+            ParseError::default()
+        );
+        let field = |range: core::ops::Range<usize>| {
+            value[range].parse().map_err(|_| ParseError::default())
+        };
+        Self::new(field(0..4)?, field(4..6)? as u8, field(6..8)? as u8)
+    }
+}
+
+fn days_in_month(year: u16, month: u8) -> u8 {
+    match month {
+        2 if year.is_multiple_of(4) && (!year.is_multiple_of(100) || year.is_multiple_of(400)) => {
+            29
+        }
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+impl Serial for Date {
+    fn serial<W: Write>(&self, out: &mut W) -> Result<(), W::Err> {
+        self.year.serial(out)?;
+        self.month.serial(out)?;
+        self.day.serial(out)
+    }
+}
+
+impl Deserial for Date {
+    fn deserial<R: Read>(source: &mut R) -> ParseResult<Self> {
+        let year = u16::deserial(source)?;
+        let month = u8::deserial(source)?;
+        let day = u8::deserial(source)?;
+        Self::new(year, month, day)
+    }
+}
+
+impl schema::SchemaType for Date {
+    fn get_type() -> schema::Type {
+        schema::Type::Struct(schema::Fields::Named(vec![
+            ("year".to_string(), schema::Type::U16),
+            ("month".to_string(), schema::Type::U8),
+            ("day".to_string(), schema::Type::U8),
+        ]))
+    }
+}
+
+// ISO-3166-1 alpha-2 codes of all officially assigned countries, in order.
+const COUNTRY_CODES: &str = "ADAEAFAGAIALAMAOAQARASATAUAWAXAZBABBBDBEBFBGBHBIBJBLBMBNBOBQBRBSBTBVBWBYBZ\
+CACCCDCFCGCHCICKCLCMCNCOCRCUCVCWCXCYCZDEDJDKDMDODZECEEEGEHERESETFIFJFKFMFOFRGAGBGDGEGFGGGHGIGLGMGNGPGQ\
+GRGSGTGUGWGYHKHMHNHRHTHUIDIEILIMINIOIQIRISITJEJMJOJPKEKGKHKIKMKNKPKRKWKYKZLALBLCLILKLRLSLTLULVLYMAMCMD\
+MEMFMGMHMKMLMMMNMOMPMQMRMSMTMUMVMWMXMYMZNANCNENFNGNINLNONPNRNUNZOMPAPEPFPGPHPKPLPMPNPRPSPTPWPYQARERORS\
+RURWSASBSCSDSESGSHSISJSKSLSMSNSOSRSSSTSVSXSYSZTCTDTFTGTHTJTKTLTMTNTOTRTTTVTWTZUAUGUMUSUYUZVAVCVEVGVIVN\
+VUWFWSYEYTZAZMZW";
+
+// An ISO-3166-1 alpha-2 country code such as `DK`. It is serialized as a
+// string so it reads naturally in JSON parameters.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct CountryCode([u8; 2]);
+
+impl CountryCode {
+    pub fn new(code: &str) -> Result<Self, ParseError> {
+        let bytes: [u8; 2] = code
+            .as_bytes()
+            .try_into()
+            .map_err(|_| ParseError::default())?;
+        ensure!(
+            COUNTRY_CODES
+                .as_bytes()
+                .chunks(2)
+                .any(|known| known == bytes),
+            ParseError::default()
+        );
+        Ok(Self(bytes))
+    }
+
+    pub fn as_str(&self) -> &str {
+        // Only ASCII codes from `COUNTRY_CODES` can be constructed
+        core::str::from_utf8(&self.0).unwrap_or_default()
+    }
+}
+
+impl Serial for CountryCode {
+    fn serial<W: Write>(&self, out: &mut W) -> Result<(), W::Err> {
+        self.as_str().serial_ctx(schema::SizeLength::U8, out)
+    }
+}
+
+impl Deserial for CountryCode {
+    fn deserial<R: Read>(source: &mut R) -> ParseResult<Self> {
+        let code = String::deserial_ctx(schema::SizeLength::U8, false, source)?;
+        Self::new(&code)
+    }
+}
+
+impl schema::SchemaType for CountryCode {
+    fn get_type() -> schema::Type {
+        schema::Type::String(schema::SizeLength::U8)
+    }
+}
+
+// The authority that issued the identity document: an ISO-3166-1 alpha-2
+// country code, optionally followed by an ISO-3166-2 subdivision such as
+// `GB-SCT`.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct IdDocumentIssuer(String);
+
+impl IdDocumentIssuer {
+    pub fn new(code: &str) -> Result<Self, ParseError> {
+        let (country, subdivision) = match code.split_once('-') {
+            Some((country, subdivision)) => (country, Some(subdivision)),
+            None => (code, None),
+        };
+        CountryCode::new(country)?;
+        ensure!(
+            subdivision.is_none_or(|subdivision| {
+                (1..=3).contains(&subdivision.len())
+                    && subdivision
+                        .bytes()
+                        .all(|b| b.is_ascii_uppercase() || b.is_ascii_digit())
+            }),
+            ParseError::default()
+        );
+        Ok(Self(code.to_string()))
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Serial for IdDocumentIssuer {
+    fn serial<W: Write>(&self, out: &mut W) -> Result<(), W::Err> {
+        self.as_str().serial_ctx(schema::SizeLength::U8, out)
+    }
+}
+
+impl Deserial for IdDocumentIssuer {
+    fn deserial<R: Read>(source: &mut R) -> ParseResult<Self> {
+        let code = String::deserial_ctx(schema::SizeLength::U8, false, source)?;
+        Self::new(&code)
+    }
+}
+
+impl schema::SchemaType for IdDocumentIssuer {
+    fn get_type() -> schema::Type {
+        schema::Type::String(schema::SizeLength::U8)
+    }
+}
+
+// Kind of document the identity was issued against.
+#[derive(Serialize, SchemaType, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum IdDocumentType {
+    NotApplicable,
+    Passport,
+    NationalIdCard,
+    DrivingLicense,
+    ImmigrationCard,
+}
+
+impl IdDocumentType {
+    // The value of the `idDocType` attribute.
+    pub fn attribute_value(&self) -> &'static str {
+        match self {
+            IdDocumentType::NotApplicable => "0",
+            IdDocumentType::Passport => "1",
+            IdDocumentType::NationalIdCard => "2",
+            IdDocumentType::DrivingLicense => "3",
+            IdDocumentType::ImmigrationCard => "4",
+        }
+    }
+}
+
+// Sex as coded by ISO-5218.
+#[derive(Serialize, SchemaType, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Sex {
+    NotKnown,
+    Male,
+    Female,
+    NotApplicable,
+}
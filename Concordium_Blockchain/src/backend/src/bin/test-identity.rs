@@ -0,0 +1,161 @@
+//! Generates synthetic identities and proofs for them, so that the id-verifier
+//! can be exercised without a wallet or a node. Run the server with
+//! `--mock-node` pointing at the fixtures directory written by `generate`.
+//! Built with `cargo run --features test-identity --bin test-identity`.
+
+use anyhow::Context;
+use clap::Parser;
+use concordium_base::id::{
+    constants::{ArCurve, AttributeKind},
+    types::{AttributeTag, GlobalContext},
+};
+use concordium_rust_sdk::types::ContractAddress;
+use id_verifier::{
+    test_identity::TestIdentity,
+    types::{ChallengeResponse, ChallengedProof},
+};
+use std::{
+    collections::BTreeMap,
+    fs,
+    io::{self, Read},
+    path::Path,
+    path::PathBuf,
+};
+
+#[derive(clap::Parser, Debug)]
+#[clap(version, author)]
+enum Command {
+    /// Creates an identity and adds its account to the mock node fixtures.
+    Generate {
+        #[clap(
+            long = "fixtures",
+            help = "Mock node fixtures directory. Created with fresh cryptographic parameters if \
+                    it does not exist."
+        )]
+        fixtures: PathBuf,
+        #[clap(long = "out", help = "File to write the identity to.")]
+        out: PathBuf,
+        #[clap(
+            long = "attribute",
+            help = "Attribute of the identity as <tag>=<value>, e.g. dob=19900101. Can be \
+                    given several times."
+        )]
+        attributes: Vec<String>,
+    },
+    /// Proves the statement of a challenge and prints the request for
+    /// /api/prove.
+    Prove {
+        #[clap(long = "fixtures", help = "Mock node fixtures directory.")]
+        fixtures: PathBuf,
+        #[clap(long = "identity", help = "Identity written by `generate`.")]
+        identity: PathBuf,
+        #[clap(
+            long = "challenge",
+            help = "File with the response of /api/challenge, or - to read it from stdin. The \
+                    statement in it is the one that is proven, as the server checks the proof \
+                    against the statement it issued with the challenge."
+        )]
+        challenge: PathBuf,
+        #[clap(
+            long = "contract",
+            help = "Contract the attestation is meant for, as <index,subindex>."
+        )]
+        contract: Option<ContractAddress>,
+    },
+}
+
+const PARAMETERS_FILE: &str = "cryptographic-parameters.json";
+const ACCOUNTS_FILE: &str = "accounts.json";
+
+fn main() -> anyhow::Result<()> {
+    match Command::parse() {
+        Command::Generate {
+            fixtures,
+            out,
+            attributes,
+        } => {
+            let alist = attributes
+                .iter()
+                .map(|attribute| parse_attribute(attribute))
+                .collect::<anyhow::Result<BTreeMap<_, _>>>()?;
+            fs::create_dir_all(&fixtures)?;
+            let global = if fixtures.join(PARAMETERS_FILE).exists() {
+                read_global_context(&fixtures)?
+            } else {
+                let global = GlobalContext::generate("id-verifier test".into());
+                fs::write(
+                    fixtures.join(PARAMETERS_FILE),
+                    serde_json::to_vec_pretty(&global)?,
+                )?;
+                global
+            };
+            let identity = TestIdentity::generate(&global, alist);
+
+            let accounts_path = fixtures.join(ACCOUNTS_FILE);
+            let mut accounts: Vec<serde_json::Value> = if accounts_path.exists() {
+                serde_json::from_slice(&fs::read(&accounts_path)?)
+                    .with_context(|| format!("Unable to parse {}.", accounts_path.display()))?
+            } else {
+                Vec::new()
+            };
+            accounts.push(serde_json::json!({
+                "address": identity.address,
+                "credentials": [identity.credential],
+            }));
+            fs::write(&accounts_path, serde_json::to_vec_pretty(&accounts)?)?;
+            fs::write(&out, serde_json::to_vec_pretty(&identity)?)?;
+            println!("{}", identity.address);
+        }
+        Command::Prove {
+            fixtures,
+            identity,
+            challenge,
+            contract,
+        } => {
+            let global = read_global_context(&fixtures)?;
+            let identity: TestIdentity = serde_json::from_slice(&fs::read(&identity)?)
+                .with_context(|| format!("Unable to parse the identity {}.", identity.display()))?;
+            let ChallengeResponse {
+                challenge,
+                statement,
+            } = read_challenge(&challenge)?;
+            let proof = identity
+                .prove(&global, &statement, &challenge)
+                .context("The identity does not satisfy the statement.")?;
+            let request = ChallengedProof {
+                challenge,
+                proof,
+                contract,
+            };
+            println!("{}", serde_json::to_string_pretty(&request)?);
+        }
+    }
+    Ok(())
+}
+
+fn parse_attribute(attribute: &str) -> anyhow::Result<(AttributeTag, AttributeKind)> {
+    let (tag, value) = attribute
+        .split_once('=')
+        .with_context(|| format!("Attribute {} is not of the form <tag>=<value>.", attribute))?;
+    let tag = tag
+        .parse()
+        .map_err(|_| anyhow::anyhow!("Unknown attribute tag {}.", tag))?;
+    Ok((tag, AttributeKind(value.into())))
+}
+
+fn read_challenge(path: &Path) -> anyhow::Result<ChallengeResponse> {
+    let bytes = if path == Path::new("-") {
+        let mut bytes = Vec::new();
+        io::stdin().read_to_end(&mut bytes)?;
+        bytes
+    } else {
+        fs::read(path).with_context(|| format!("Unable to read {}.", path.display()))?
+    };
+    serde_json::from_slice(&bytes).context("Unable to parse the challenge response.")
+}
+
+fn read_global_context(fixtures: &Path) -> anyhow::Result<GlobalContext<ArCurve>> {
+    let path = fixtures.join(PARAMETERS_FILE);
+    let bytes = fs::read(&path).with_context(|| format!("Unable to read {}.", path.display()))?;
+    serde_json::from_slice(&bytes).with_context(|| format!("Unable to parse {}.", path.display()))
+}
@@ -1,30 +1,43 @@
-use crate::crypto_common::base16_encode_string;
+use crate::keys::KeySet;
+use crate::node::NodeBackend;
 use crate::types::*;
+use crate::verify::verify_statement;
 use concordium_rust_sdk::{
-    common::{self as crypto_common, types::KeyPair},
-    id::{
-        constants::{ArCurve, AttributeKind},
-        id_proof_types::Statement,
-        types::{AccountAddress, AccountCredentialWithoutProofs},
-    },
-    v2::BlockIdentifier,
+    common as crypto_common,
+    id::types::{AccountAddress, AccountCredentialWithoutProofs},
 };
 use log::warn;
 use rand::Rng;
+use sha2::{Digest, Sha256};
 use std::convert::Infallible;
-use std::time::SystemTime;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::sync::watch;
 use warp::{http::StatusCode, Rejection};
 
-static CHALLENGE_EXPIRY_SECONDS: u64 = 600;
-static CLEAN_INTERVAL_SECONDS: u64 = 600;
+static ATTESTATION_VALIDITY_SECONDS: u64 = 3600;
+
+pub async fn handle_get_statement(
+    state: Server,
+    policy: String,
+) -> Result<impl warp::Reply, Rejection> {
+    let statement = state
+        .policies
+        .get(&policy)
+        .ok_or(InjectStatementError::UnknownPolicy)?
+        .statement()
+        .ok_or(InjectStatementError::InvalidPolicy)?;
+    Ok(warp::reply::json(&statement))
+}
 
 pub async fn handle_get_challenge(
     state: Server,
+    policy: String,
     address: AccountAddress,
 ) -> Result<impl warp::Reply, Rejection> {
     let state = state.clone();
     log::debug!("Parsed statement. Generating challenge");
-    match get_challenge_worker(state, address).await {
+    match get_challenge_worker(state, policy, address).await {
         Ok(r) => Ok(warp::reply::json(&r)),
         Err(e) => {
             warn!("Request is invalid {:#?}.", e);
@@ -36,39 +49,45 @@ pub async fn handle_get_challenge(
 /// A common function that produces a challenge and adds it to the state.
 async fn get_challenge_worker(
     state: Server,
+    policy: String,
     address: AccountAddress,
 ) -> Result<ChallengeResponse, InjectStatementError> {
+    let statement = state
+        .policies
+        .get(&policy)
+        .ok_or(InjectStatementError::UnknownPolicy)?
+        .statement()
+        .ok_or(InjectStatementError::InvalidPolicy)?;
     let mut challenge = [0u8; 32];
     rand::thread_rng().fill(&mut challenge[..]);
-    let mut sm = state
-        .challenges
-        .lock()
-        .map_err(|_| InjectStatementError::LockingError)?;
     log::debug!("Generated challenge: {:?}", challenge);
     let challenge = Challenge(challenge);
 
-    sm.insert(
-        base16_encode_string(&challenge.0),
+    state.challenges.insert(
+        &challenge,
         ChallengeStatus {
             address,
             created_at: SystemTime::now(),
+            policy,
+            statement: statement.clone(),
         },
-    );
-    Ok(ChallengeResponse { challenge })
+    )?;
+    Ok(ChallengeResponse {
+        challenge,
+        statement,
+    })
 }
 
 
 pub async fn handle_provide_proof(
-    client: concordium_rust_sdk::v2::Client,
+    node: Arc<dyn NodeBackend>,
     state: Server,
-    statement: Statement<ArCurve, AttributeKind>,
+    policy: String,
     request: ChallengedProof,
-    key_pair: KeyPair,
+    keys: Arc<KeySet>,
 ) -> Result<impl warp::Reply, Rejection> {
-    let client = client.clone();
     let state = state.clone();
-    let statement = statement.clone();
-    match check_proof_worker(client, state, request, statement, key_pair).await {
+    match check_proof_worker(node.as_ref(), state, request, policy, &keys).await {
         Ok(r) => Ok(warp::reply::json(&r)),
         Err(e) => {
             warn!("Request is invalid {:#?}.", e);
@@ -79,65 +98,170 @@ pub async fn handle_provide_proof(
 
 /// A common function that validates the cryptographic proofs in the request.
 async fn check_proof_worker(
-    mut client: concordium_rust_sdk::v2::Client,
+    node: &dyn NodeBackend,
     state: Server,
     request: ChallengedProof,
-    statement: Statement<ArCurve, AttributeKind>,
-    key_pair: KeyPair,
-) -> Result<String, InjectStatementError> {
-    let status = {
-        let challenges = state
-            .challenges
-            .lock()
-            .map_err(|_| InjectStatementError::LockingError)?;
-
-        challenges
-            .get(&base16_encode_string(&request.challenge.0))
-            .ok_or(InjectStatementError::UnknownSession)?
-            .clone()
-    };
+    policy: String,
+    keys: &KeySet,
+) -> Result<ProofResponse, InjectStatementError> {
+    if !state.policies.contains_key(&policy) {
+        return Err(InjectStatementError::UnknownPolicy);
+    }
+    let status = state
+        .challenges
+        .get(&request.challenge)?
+        .ok_or(InjectStatementError::UnknownSession)?;
+    // The sweeper only runs periodically, so expired challenges can still be
+    // in the store.
+    if status.is_expired(state.challenge_expiry) {
+        return Err(InjectStatementError::ExpiredChallenge);
+    }
+    // A proof for one policy must not be accepted for another.
+    if status.policy != policy {
+        return Err(InjectStatementError::WrongPolicy);
+    }
 
     let cred_id = request.proof.credential;
-    let acc_info = client
-        .get_account_info(&status.address.into(), BlockIdentifier::LastFinal)
-        .await?;
-
-    // TODO Check remaining credentials
-    let credential = acc_info
-        .response
-        .account_credentials
-        .get(&0.into())
-        .ok_or(InjectStatementError::Credential)?;
-
-        //The line below  makes sure that the credential sent by the user is the same, as the one that the account has.
-    if crypto_common::to_bytes(credential.value.cred_id()) != crypto_common::to_bytes(&cred_id) {
-        return Err(InjectStatementError::Credential);
-    }
+    let proof = request.proof.versioned_proof()?;
+    let credentials = node.account_credentials(&status.address).await?;
+
+    // The proof can be made with any of the account's credentials.
+    let cred_id_bytes = crypto_common::to_bytes(&cred_id);
+    let credential = credentials
+        .iter()
+        .find(|credential| crypto_common::to_bytes(credential.cred_id()) == cred_id_bytes)
+        .ok_or(InjectStatementError::CredentialNotOnAccount)?;
 
-    let commitments = match &credential.value {
+    let commitments = match credential {
         AccountCredentialWithoutProofs::Initial { icdv: _, .. } => {
             return Err(InjectStatementError::NotAllowed);
         }
         AccountCredentialWithoutProofs::Normal { commitments, .. } => commitments,
     };
 
-    let mut challenges = state
-        .challenges
-        .lock()
-        .map_err(|_| InjectStatementError::LockingError)?;
-
         // we verify the proof with this part and respond back with the result which is the signature
-    if statement.verify(
+    let statement = &status.statement;
+    if let Err(e) = verify_statement(
+        statement,
         &request.challenge.0,
         &state.global_context,
         cred_id.as_ref(),
         commitments,
-        &request.proof.proof.value, // TODO: Check version.
+        &proof,
     ) {
-        challenges.remove(&base16_encode_string(&request.challenge.0));
-        let sig = key_pair.sign(&acc_info.response.account_address.0);
-        Ok(hex::encode_upper(sig.sig))
+        warn!("Proof does not verify: {}", e);
+        return Err(InjectStatementError::InvalidProofs);
+    }
+    // Consume the challenge. If a concurrent request already did, this
+    // proof must not be accepted as well.
+    if state.challenges.take(&request.challenge)?.is_none() {
+        return Err(InjectStatementError::UnknownSession);
+    }
+    let issued_at = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_millis() as u64);
+    let key = keys
+        .signing_key(issued_at)
+        .ok_or(InjectStatementError::NoSigningKey)?;
+    let attestation = Attestation {
+        account: status.address,
+        policy_hash: policy_hash(&status.policy),
+        challenge: request.challenge,
+        issued_at,
+        expires_at: issued_at + ATTESTATION_VALIDITY_SECONDS * 1000,
+        contract: request.contract,
+        key_id: key.id.clone(),
+    };
+    let sig = key.key_pair.sign(&attestation.signed_bytes());
+    Ok(ProofResponse {
+        attestation,
+        signature: hex::encode_upper(sig.sig),
+    })
+}
+
+/// Publishes the public keys, so that consumers can look up the key of a
+/// signature by its id.
+pub async fn handle_get_keys(keys: Arc<KeySet>) -> Result<impl warp::Reply, Rejection> {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_millis() as u64);
+    Ok(warp::reply::json(&keys.jwks(now)))
+}
+
+/// Turns rejections into a JSON [`ErrorResponse`] with a matching status code.
+pub async fn handle_rejection(err: Rejection) -> Result<impl warp::Reply, Infallible> {
+    let (status, code, message) = if err.is_not_found() {
+        (StatusCode::NOT_FOUND, "NOT_FOUND", "Not found.".to_string())
+    } else if let Some(e) = err.find::<InjectStatementError>() {
+        (e.status_code(), e.error_code(), e.to_string())
+    } else if let Some(e) = err.find::<warp::filters::body::BodyDeserializeError>() {
+        (StatusCode::BAD_REQUEST, "MALFORMED_REQUEST", e.to_string())
+    } else if let Some(e) = err.find::<warp::reject::InvalidQuery>() {
+        (StatusCode::BAD_REQUEST, "MALFORMED_REQUEST", e.to_string())
+    } else if let Some(e) = err.find::<warp::reject::PayloadTooLarge>() {
+        (
+            StatusCode::PAYLOAD_TOO_LARGE,
+            "PAYLOAD_TOO_LARGE",
+            e.to_string(),
+        )
+    } else if let Some(e) = err.find::<warp::reject::LengthRequired>() {
+        (
+            StatusCode::LENGTH_REQUIRED,
+            "LENGTH_REQUIRED",
+            e.to_string(),
+        )
+    } else if let Some(e) = err.find::<warp::reject::UnsupportedMediaType>() {
+        (
+            StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            "UNSUPPORTED_MEDIA_TYPE",
+            e.to_string(),
+        )
+    } else if let Some(e) = err.find::<warp::reject::MethodNotAllowed>() {
+        (
+            StatusCode::METHOD_NOT_ALLOWED,
+            "METHOD_NOT_ALLOWED",
+            e.to_string(),
+        )
     } else {
-        Err(InjectStatementError::InvalidProofs)
+        warn!("Unhandled rejection {:?}.", err);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "INTERNAL_ERROR",
+            "Internal error.".to_string(),
+        )
+    };
+    let response = ErrorResponse {
+        code: code.to_string(),
+        message,
+    };
+    Ok(warp::reply::with_status(
+        warp::reply::json(&response),
+        status,
+    ))
+}
+
+/// Periodically removes challenges that are older than the expiry, so that
+/// abandoned sessions do not accumulate. Sweeps twice per expiry window, so an
+/// expired challenge lingers for at most half the window. A failing sweep is
+/// logged and retried on the next tick. Runs until `shutdown` is signalled.
+pub async fn handle_clean_state(state: Server, mut shutdown: watch::Receiver<()>) {
+    let period = (state.challenge_expiry / 2).max(Duration::from_secs(1));
+    let mut interval = tokio::time::interval(period);
+    loop {
+        tokio::select! {
+            _ = interval.tick() => {}
+            _ = shutdown.changed() => break,
+        }
+        match state.challenges.remove_expired(state.challenge_expiry) {
+            Ok(0) => {}
+            Ok(removed) => log::info!("Removed {} expired challenges.", removed),
+            Err(e) => log::error!("Could not remove expired challenges: {}", e),
+        }
     }
+    log::debug!("Stopped cleaning up challenges.");
+}
+
+/// Hash identifying the policy an attestation was issued for.
+fn policy_hash(policy: &str) -> [u8; 32] {
+    Sha256::digest(policy.as_bytes()).into()
 }
@@ -0,0 +1,278 @@
+use anyhow::Context;
+use base64::Engine;
+use concordium_base::common::encryption::{self, EncryptedData, Password};
+use concordium_rust_sdk::{
+    common::types::{CredentialIndex, KeyIndex, KeyPair},
+    types::WalletAccount,
+};
+use sha2::{Digest, Sha256};
+use std::{
+    collections::BTreeSet,
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// Environment variable the keystore password is read from, so that it does
+/// not show up in the process list.
+pub const KEYSTORE_PASSWORD_ENV: &str = "ID_VERIFIER_KEYSTORE_PASSWORD";
+
+/// Loads a key pair from a JSON file of the form
+/// `{ "signKey": "<hex>", "verifyKey": "<hex>" }`.
+pub fn load_key_file(path: &Path) -> anyhow::Result<KeyPair> {
+    let bytes = fs::read(path)
+        .with_context(|| format!("Unable to read the key file {}.", path.display()))?;
+    parse_key_pair(&bytes).with_context(|| format!("Invalid key file {}.", path.display()))
+}
+
+/// Loads the first key of the first credential from a Concordium wallet
+/// export, or a genesis account file.
+pub fn load_wallet_export(path: &Path) -> anyhow::Result<KeyPair> {
+    let mut wallet = WalletAccount::from_json_file(path)
+        .with_context(|| format!("Invalid wallet export {}.", path.display()))?;
+    let key_pair = wallet
+        .keys
+        .keys
+        .remove(&CredentialIndex { index: 0 })
+        .and_then(|mut credential| credential.keys.remove(&KeyIndex(0)))
+        .with_context(|| {
+            format!(
+                "The wallet export {} has no key 0 for credential 0.",
+                path.display()
+            )
+        })?;
+    check_key_pair(key_pair).with_context(|| format!("Invalid wallet export {}.", path.display()))
+}
+
+/// Loads a key file that was encrypted with [`encrypt_key_file`].
+pub fn load_keystore(path: &Path, password: &str) -> anyhow::Result<KeyPair> {
+    let bytes = fs::read(path)
+        .with_context(|| format!("Unable to read the keystore {}.", path.display()))?;
+    let encrypted: EncryptedData = serde_json::from_slice(&bytes)
+        .with_context(|| format!("Invalid keystore {}.", path.display()))?;
+    let plaintext = encryption::decrypt(&Password::from(password.to_string()), &encrypted)
+        .map_err(|_| {
+            anyhow::anyhow!(
+                "Unable to decrypt the keystore {}. Is the password correct?",
+                path.display()
+            )
+        })?;
+    parse_key_pair(&plaintext).with_context(|| format!("Invalid keystore {}.", path.display()))
+}
+
+/// Encrypts a key file with a password, for use with [`load_keystore`].
+pub fn encrypt_key_file(key_file: &Path, password: &str) -> anyhow::Result<EncryptedData> {
+    // Fail now rather than when the keystore is first used.
+    load_key_file(key_file)?;
+    let plaintext = fs::read(key_file)?;
+    Ok(encryption::encrypt(
+        &Password::from(password.to_string()),
+        &plaintext,
+        &mut rand_07::thread_rng(),
+    ))
+}
+
+fn parse_key_pair(bytes: &[u8]) -> anyhow::Result<KeyPair> {
+    let key_pair: KeyPair = serde_json::from_slice(bytes)
+        .context("Expected hex encoded \"signKey\" and \"verifyKey\".")?;
+    check_key_pair(key_pair)
+}
+
+/// The keys are stored separately, so a file can contain a verify key that
+/// does not belong to the sign key. Signatures would then not verify.
+fn check_key_pair(key_pair: KeyPair) -> anyhow::Result<KeyPair> {
+    let public = ed25519_dalek::PublicKey::from(&key_pair.secret);
+    anyhow::ensure!(
+        public == key_pair.public,
+        "The verify key does not belong to the sign key."
+    );
+    Ok(key_pair)
+}
+
+/// A key the server signs attestations with.
+pub struct SigningKey {
+    /// Identifies the key in signatures and in `/api/keys`.
+    pub id: String,
+    pub key_pair: KeyPair,
+    /// Unix time in milliseconds from which the key is used for signing.
+    pub valid_from: u64,
+    /// Unix time in milliseconds from which the key is retired. Retired keys
+    /// are still published, so that attestations they signed can be checked.
+    pub valid_until: Option<u64>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum KeyStatus {
+    /// Not used for signing yet. Published so consumers can add it ahead of
+    /// the rotation.
+    Pending,
+    Active,
+    Retired,
+}
+
+impl SigningKey {
+    pub fn status(&self, now: u64) -> KeyStatus {
+        if now < self.valid_from {
+            KeyStatus::Pending
+        } else if self.valid_until.is_some_and(|until| now >= until) {
+            KeyStatus::Retired
+        } else {
+            KeyStatus::Active
+        }
+    }
+}
+
+/// The keys the server holds. Of the active keys, the one that became valid
+/// last is used for signing, so a new key takes over as soon as its validity
+/// starts, even if the previous one has not been retired yet.
+pub struct KeySet {
+    keys: Vec<SigningKey>,
+}
+
+impl KeySet {
+    pub fn new(keys: Vec<SigningKey>) -> anyhow::Result<Self> {
+        let mut ids = BTreeSet::new();
+        for key in &keys {
+            anyhow::ensure!(ids.insert(&key.id), "Duplicate key id {}.", key.id);
+            anyhow::ensure!(
+                key.valid_until.is_none_or(|until| key.valid_from < until),
+                "The key {} is valid until before it is valid from.",
+                key.id
+            );
+        }
+        Ok(Self { keys })
+    }
+
+    /// A set of one key that is always valid. Its id is derived from the
+    /// public key.
+    pub fn single(key_pair: KeyPair) -> Self {
+        Self {
+            keys: vec![SigningKey {
+                id: key_id(&key_pair.public),
+                key_pair,
+                valid_from: 0,
+                valid_until: None,
+            }],
+        }
+    }
+
+    /// The key to sign with at `now`, if any key is active.
+    pub fn signing_key(&self, now: u64) -> Option<&SigningKey> {
+        self.keys
+            .iter()
+            .filter(|key| key.status(now) == KeyStatus::Active)
+            .max_by_key(|key| key.valid_from)
+    }
+
+    pub fn get(&self, id: &str) -> Option<&SigningKey> {
+        self.keys.iter().find(|key| key.id == id)
+    }
+
+    /// The public keys in the style of a JSON Web Key Set (RFC 7517, 8037).
+    pub fn jwks(&self, now: u64) -> Jwks {
+        let keys = self
+            .keys
+            .iter()
+            .map(|key| Jwk {
+                kty: "OKP",
+                crv: "Ed25519",
+                alg: "EdDSA",
+                key_use: "sig",
+                kid: key.id.clone(),
+                x: base64::engine::general_purpose::URL_SAFE_NO_PAD
+                    .encode(key.key_pair.public.as_bytes()),
+                verify_key: hex::encode(key.key_pair.public.as_bytes()),
+                status: key.status(now),
+                valid_from: key.valid_from,
+                valid_until: key.valid_until,
+            })
+            .collect();
+        Jwks { keys }
+    }
+}
+
+#[derive(serde::Serialize, Debug)]
+pub struct Jwks {
+    pub keys: Vec<Jwk>,
+}
+
+/// A public key. Besides the JWK members, it has the key in hex, which is
+/// what contracts are initialized with, and its validity.
+#[derive(serde::Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct Jwk {
+    pub kty: &'static str,
+    pub crv: &'static str,
+    pub alg: &'static str,
+    #[serde(rename = "use")]
+    pub key_use: &'static str,
+    pub kid: String,
+    /// Base64url encoded public key.
+    pub x: String,
+    pub verify_key: String,
+    pub status: KeyStatus,
+    pub valid_from: u64,
+    pub valid_until: Option<u64>,
+}
+
+/// Id of a key that was not given one: the first 8 bytes of the SHA-256 of
+/// the public key, in hex.
+pub fn key_id(public: &ed25519_dalek::PublicKey) -> String {
+    hex::encode(&Sha256::digest(public.as_bytes())[..8])
+}
+
+/// An entry of a key set file.
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct KeySetEntry {
+    id: Option<String>,
+    #[serde(flatten)]
+    source: KeySource,
+    #[serde(default)]
+    valid_from: u64,
+    valid_until: Option<u64>,
+}
+
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+enum KeySource {
+    KeyFile(PathBuf),
+    WalletExport(PathBuf),
+    Keystore(PathBuf),
+}
+
+#[derive(serde::Deserialize)]
+struct KeySetFile {
+    keys: Vec<KeySetEntry>,
+}
+
+/// Loads a key set file of the form
+/// `{ "keys": [{ "id": "2026-10", "keyFile": "verifier.json", "validFrom": <ms>, "validUntil": <ms> }] }`.
+/// Instead of `keyFile`, an entry can have a `walletExport` or a `keystore`.
+/// Paths are relative to the key set file. `password` is only called if the
+/// set has a keystore.
+pub fn load_key_set(
+    path: &Path,
+    password: impl Fn() -> anyhow::Result<String>,
+) -> anyhow::Result<KeySet> {
+    let bytes = fs::read(path)
+        .with_context(|| format!("Unable to read the key set {}.", path.display()))?;
+    let file: KeySetFile = serde_json::from_slice(&bytes)
+        .with_context(|| format!("Invalid key set {}.", path.display()))?;
+    let dir = path.parent().unwrap_or_else(|| Path::new(""));
+    let mut keys = Vec::with_capacity(file.keys.len());
+    for entry in file.keys {
+        let key_pair = match entry.source {
+            KeySource::KeyFile(file) => load_key_file(&dir.join(file))?,
+            KeySource::WalletExport(file) => load_wallet_export(&dir.join(file))?,
+            KeySource::Keystore(file) => load_keystore(&dir.join(file), &password()?)?,
+        };
+        keys.push(SigningKey {
+            id: entry.id.unwrap_or_else(|| key_id(&key_pair.public)),
+            key_pair,
+            valid_from: entry.valid_from,
+            valid_until: entry.valid_until,
+        });
+    }
+    KeySet::new(keys).with_context(|| format!("Invalid key set {}.", path.display()))
+}
@@ -0,0 +1,88 @@
+pub mod handlers;
+pub mod keys;
+pub mod node;
+pub mod policy;
+pub mod store;
+#[cfg(feature = "test-identity")]
+pub mod test_identity;
+pub mod types;
+pub mod verify;
+use crate::handlers::*;
+use crate::keys::KeySet;
+use crate::node::NodeBackend;
+use crate::types::*;
+
+use std::sync::Arc;
+use warp::Filter;
+
+/// The HTTP API of the server. Rejections are turned into JSON errors.
+pub fn routes(
+    state: Server,
+    node: Arc<dyn NodeBackend>,
+    keys: Arc<KeySet>,
+) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+    let default_policy = state.default_policy.clone();
+    let prove_state = state.clone();
+    let challenge_state = state.clone();
+    let statement_state = state;
+
+    let cors = warp::cors()
+        .allow_any_origin()
+        .allow_header("Content-Type")
+        .allow_method("POST");
+
+    // 1a. get challenge
+    let get_challenge = warp::get()
+        .and(policy_path("challenge", default_policy.clone()))
+        .and(warp::query::<WithAccountAddress>())
+        .and_then(move |policy: String, query: WithAccountAddress| {
+            handle_get_challenge(challenge_state.clone(), policy, query.address)
+        });
+
+    // 1b. get statement
+    let get_statement = warp::get()
+        .and(policy_path("statement", default_policy.clone()))
+        .and_then(move |policy: String| handle_get_statement(statement_state.clone(), policy));
+
+    // 1c. get the public keys signatures can be checked with
+    let keys_state = keys.clone();
+    let get_keys = warp::get()
+        .and(warp::path!("api" / "keys"))
+        .and_then(move || handle_get_keys(keys_state.clone()));
+
+    // 2. Provide proof
+    let provide_proof = warp::post()
+        .and(warp::filters::body::content_length_limit(50 * 1024))
+        .and(policy_path("prove", default_policy))
+        .and(warp::body::json())
+        .and_then(move |policy: String, request: ChallengedProof| {
+            handle_provide_proof(
+                node.clone(),
+                prove_state.clone(),
+                policy,
+                request,
+                keys.clone(),
+            )
+        });
+
+    get_challenge
+        .or(get_statement)
+        .or(get_keys)
+        .or(provide_proof)
+        .recover(handle_rejection)
+        .with(cors)
+        .with(warp::trace::request())
+}
+
+/// Matches `/api/<route>/<policy>`, and `/api/<route>` for the default policy
+/// so that clients written before there were named policies keep working.
+fn policy_path(
+    route: &'static str,
+    default_policy: String,
+) -> impl Filter<Extract = (String,), Error = warp::Rejection> + Clone {
+    let default = warp::any().map(move || default_policy.clone());
+    warp::path("api")
+        .and(warp::path(route))
+        .and(warp::path::param::<String>().or(default).unify())
+        .and(warp::path::end())
+}
@@ -1,42 +1,80 @@
-mod handlers;
-mod types;
-use crate::handlers::*;
-use crate::types::*;
+use id_verifier::handlers::handle_clean_state;
+use id_verifier::keys::{
+    encrypt_key_file, load_key_file, load_key_set, load_keystore, load_wallet_export, KeySet,
+    KEYSTORE_PASSWORD_ENV,
+};
+use id_verifier::node::{Credential, GrpcNode, MockNode, NodeBackend};
+use id_verifier::policy::{load_policies, load_policy};
+use id_verifier::routes;
+#[cfg(feature = "sled")]
+use id_verifier::store::SledStore;
+use id_verifier::store::{ChallengeStore, MemoryStore};
+use id_verifier::types::{ChallengedProof, Server};
+use id_verifier::verify::verify_statement;
 
+use anyhow::Context;
 use clap::Parser;
-use concordium_rust_sdk::common::types::KeyPair;
 use concordium_rust_sdk::{
-    common::{self as crypto_common},
+    common::{self as crypto_common, Versioned},
     id::{
-        constants::{ArCurve, AttributeKind},
-        id_proof_types::Statement,
+        constants::ArCurve,
+        types::{CredentialDeploymentCommitments, GlobalContext},
     },
-    v2::BlockIdentifier,
+    types::CredentialRegistrationID,
 };
 use log::info;
 use std::{
-    collections::HashMap,
-    sync::{Arc, Mutex},
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::{Duration, SystemTime, UNIX_EPOCH},
 };
-use warp::Filter;
 
 /// Structure used to receive the correct command line arguments.
 #[derive(clap::Parser, Debug)]
 #[clap(arg_required_else_help(true))]
 #[clap(version, author)]
+#[clap(subcommand_negates_reqs = true, args_conflicts_with_subcommands = true)]
+#[clap(group(
+    clap::ArgGroup::new("keys")
+        .required(true)
+        .args(&["key-file", "wallet-export", "keystore", "key-set"])
+))]
 struct IdVerifierConfig {
+    #[clap(subcommand)]
+    command: Option<Command>,
     #[clap(
         long = "node",
         help = "GRPC V2 interface of the node.",
         default_value = "http://localhost:20000"
     )]
     endpoint: concordium_rust_sdk::v2::Endpoint,
+    #[clap(
+        long = "mock-node",
+        help = "Directory with fixture files to answer node queries from instead of a node. \
+                For testing only."
+    )]
+    mock_node: Option<PathBuf>,
     #[clap(
         long = "port",
         default_value = "8100",
         help = "Port on which the server will listen on."
     )]
     port: u16,
+    #[clap(
+        long = "challenge-expiry",
+        default_value = "600",
+        help = "Number of seconds a challenge can be used for after it was issued."
+    )]
+    challenge_expiry: u64,
+    #[cfg(feature = "sled")]
+    #[clap(
+        long = "challenge-db",
+        help = "Database directory in which issued challenges are kept, so that they survive \
+                restarts. If not given, challenges are only kept in memory."
+    )]
+    challenge_db: Option<PathBuf>,
     #[structopt(
         long = "log-level",
         default_value = "debug",
@@ -44,105 +82,242 @@ struct IdVerifierConfig {
     )]
     log_level: log::LevelFilter,
     #[clap(
-        long = "statement",
-        help = "The statement that the server accepts proofs for."
+        long = "policies",
+        default_value = "config",
+        help = "Directory with a <name>.json statement for each policy the server accepts \
+                proofs for."
     )]
-    statement: String,
-    #[structopt(
-        long = "sign-key",
-        help = "Sign key of the first credential of the signer"
+    policies: PathBuf,
+    #[clap(
+        long = "default-policy",
+        default_value = "age18",
+        help = "Policy used by /api/challenge, /api/statement and /api/prove when no policy name \
+                is given."
     )]
-    sign_key: String,
-    #[structopt(
-        long = "verify-key",
-        help = "Verify key of the first credential of the signer"
+    default_policy: String,
+    #[clap(
+        long = "key-file",
+        help = "JSON file with the hex encoded signKey and verifyKey the server signs with."
+    )]
+    key_file: Option<PathBuf>,
+    #[clap(
+        long = "wallet-export",
+        help = "Concordium wallet export. The first key of the first credential is used."
     )]
-    verify_key: String,
+    wallet_export: Option<PathBuf>,
+    #[clap(
+        long = "keystore",
+        help = "Key file encrypted with the encrypt-key subcommand. The password is read from \
+                ID_VERIFIER_KEYSTORE_PASSWORD."
+    )]
+    keystore: Option<PathBuf>,
+    #[clap(
+        long = "key-set",
+        help = "JSON file with the keys to rotate between, of the form {\"keys\": [{\"id\", \
+                \"keyFile\" | \"walletExport\" | \"keystore\", \"validFrom\", \
+                \"validUntil\"}]}. Times are Unix milliseconds, paths relative to the file."
+    )]
+    key_set: Option<PathBuf>,
 }
 
+#[derive(clap::Subcommand, Debug)]
+enum Command {
+    /// Checks a proof offline, without starting the server, and reports which
+    /// part of the statement failed.
+    Verify(VerifyArgs),
+    /// Encrypts a key file with the password in ID_VERIFIER_KEYSTORE_PASSWORD,
+    /// for use with --keystore.
+    EncryptKey {
+        #[clap(long = "key-file", help = "The key file to encrypt.")]
+        key_file: PathBuf,
+        #[clap(long = "out", help = "File to write the keystore to.")]
+        out: PathBuf,
+    },
+}
 
+#[derive(clap::Args, Debug)]
+struct VerifyArgs {
+    #[clap(
+        long = "statement",
+        help = "The statement or policy file. For age policies use the statement returned with \
+                the challenge, since the dates depend on the day the challenge was issued."
+    )]
+    statement: PathBuf,
+    #[clap(long = "proof", help = "The request that was posted to /api/prove.")]
+    proof: PathBuf,
+    #[clap(
+        long = "credential",
+        help = "The credential commitments, or an account info dump of the account."
+    )]
+    credential: PathBuf,
+    #[clap(long = "global", help = "The cryptographic parameters of the chain.")]
+    global: PathBuf,
+}
+
+/// Runs the server's proof verification on files.
+fn verify_offline(args: VerifyArgs) -> anyhow::Result<()> {
+    let statement = load_policy(&args.statement)?
+        .statement()
+        .context("The statement file does not describe a valid statement.")?;
+    let request: ChallengedProof = read_json(&args.proof)?;
+    let global: GlobalContext<ArCurve> = read_json(&args.global)?;
+    let commitments = load_commitments(&args.credential, &request.proof.credential)?;
+    let proof = request.proof.versioned_proof()?;
+    verify_statement(
+        &statement,
+        &request.challenge.0,
+        &global,
+        request.proof.credential.as_ref(),
+        &commitments,
+        &proof,
+    )?;
+    println!("The proof verifies.");
+    Ok(())
+}
+
+/// Reads the commitments of the credential from either a commitments file or
+/// the `accountCredentials` of an account info dump.
+fn load_commitments(
+    path: &Path,
+    cred_id: &CredentialRegistrationID,
+) -> anyhow::Result<CredentialDeploymentCommitments<ArCurve>> {
+    let json: serde_json::Value = read_json(path)?;
+    let credentials = match json.get("accountCredentials") {
+        Some(credentials) => credentials.clone(),
+        None => {
+            return serde_json::from_value(json).with_context(|| {
+                format!(
+                    "{} is neither an account info dump nor credential commitments.",
+                    path.display()
+                )
+            })
+        }
+    };
+    let credentials: BTreeMap<String, Versioned<Credential>> =
+        serde_json::from_value(credentials).context("Unable to parse the account credentials.")?;
+    let cred_id_bytes = crypto_common::to_bytes(cred_id);
+    let credential = credentials
+        .into_values()
+        .find(|credential| crypto_common::to_bytes(credential.value.cred_id()) == cred_id_bytes)
+        .with_context(|| format!("The credential {} is not on the account.", cred_id))?;
+    match credential.value {
+        Credential::Initial { .. } => {
+            anyhow::bail!("The credential {} is an initial credential.", cred_id)
+        }
+        Credential::Normal { commitments, .. } => Ok(commitments),
+    }
+}
+
+/// Loads the keys the server signs with from the configured source.
+fn load_signing_keys(app: &IdVerifierConfig) -> anyhow::Result<KeySet> {
+    if let Some(path) = &app.key_file {
+        load_key_file(path).map(KeySet::single)
+    } else if let Some(path) = &app.wallet_export {
+        load_wallet_export(path).map(KeySet::single)
+    } else if let Some(path) = &app.keystore {
+        load_keystore(path, &keystore_password()?).map(KeySet::single)
+    } else if let Some(path) = &app.key_set {
+        load_key_set(path, keystore_password)
+    } else {
+        anyhow::bail!("No signing key configured.")
+    }
+}
+
+fn keystore_password() -> anyhow::Result<String> {
+    std::env::var(KEYSTORE_PASSWORD_ENV)
+        .with_context(|| format!("{} is not set.", KEYSTORE_PASSWORD_ENV))
+}
+
+fn read_json<T: serde::de::DeserializeOwned>(path: &Path) -> anyhow::Result<T> {
+    let bytes = fs::read(path).with_context(|| format!("Unable to read {}.", path.display()))?;
+    serde_json::from_slice(&bytes).with_context(|| format!("Unable to parse {}.", path.display()))
+}
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
     let app = IdVerifierConfig::parse();
+    match app.command {
+        Some(Command::Verify(args)) => return verify_offline(args),
+        Some(Command::EncryptKey { key_file, out }) => {
+            let keystore = encrypt_key_file(&key_file, &keystore_password()?)?;
+            fs::write(&out, serde_json::to_vec_pretty(&keystore)?)?;
+            return Ok(());
+        }
+        None => {}
+    }
     let mut log_builder = env_logger::Builder::new();
     // only log the current module (main).
     log_builder.filter_level(app.log_level); // filter filter_module(module_path!(), app.log_level);
     log_builder.init();
-    let statement: Statement<ArCurve, AttributeKind> = serde_json::from_str(&app.statement)?;
+    let keys = load_signing_keys(&app)?;
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_millis() as u64);
+    let signing_key = keys
+        .signing_key(now)
+        .context("None of the signing keys is currently valid.")?;
+    info!("Signing with key {}.", signing_key.id);
+    let policies = load_policies(&app.policies)?;
+    info!(
+        "Loaded policies: {}.",
+        policies.keys().cloned().collect::<Vec<_>>().join(", ")
+    );
+    anyhow::ensure!(
+        policies.contains_key(&app.default_policy),
+        "The default policy {} is not one of the loaded policies.",
+        app.default_policy
+    );
 
-    let mut client = concordium_rust_sdk::v2::Client::new(app.endpoint).await?;
-    let global_context = client
-        .get_cryptographic_parameters(BlockIdentifier::LastFinal)
-        .await?
-        .response;
+    let node: Arc<dyn NodeBackend> = match &app.mock_node {
+        Some(dir) => {
+            log::warn!("Using the mock node in {}.", dir.display());
+            Arc::new(MockNode::load(dir)?)
+        }
+        None => Arc::new(GrpcNode::connect(app.endpoint.clone()).await?),
+    };
+    let global_context = node.global_context().await?;
 
     log::debug!("Acquired data from the node.");
 
+    #[cfg(feature = "sled")]
+    let challenges: Arc<dyn ChallengeStore> = match &app.challenge_db {
+        Some(path) => Arc::new(SledStore::open(path).with_context(|| {
+            format!("Unable to open the challenge database {}.", path.display())
+        })?),
+        None => Arc::new(MemoryStore::default()),
+    };
+    #[cfg(not(feature = "sled"))]
+    let challenges: Arc<dyn ChallengeStore> = Arc::new(MemoryStore::default());
+
     let state = Server {
-        challenges: Arc::new(Mutex::new(HashMap::new())),
+        challenges,
         global_context: Arc::new(global_context),
+        challenge_expiry: Duration::from_secs(app.challenge_expiry),
+        policies: Arc::new(policies),
+        default_policy: app.default_policy,
     };
-    let prove_state = state.clone();
-    let challenge_state = state.clone();
-
-    let cors = warp::cors()
-        .allow_any_origin()
-        .allow_header("Content-Type")
-        .allow_method("POST");
-
-    // 1a. get challenge
-    let get_challenge = warp::get()
-        .and(warp::path!("api" / "challenge"))
-        .and(warp::query::<WithAccountAddress>())
-        .and_then(move |query: WithAccountAddress| {
-            handle_get_challenge(challenge_state.clone(), query.address)
-        });
-
-    // 1b. get statement
-    // change it to check older than 18 only.
-    let get_statement = warp::get()
-        .and(warp::path!("api" / "statement"))
-        .map(move || warp::reply::json(&app.statement));
-
-    // 2. Provide proof
-    let provide_proof = warp::post()
-        .and(warp::filters::body::content_length_limit(50 * 1024))
-        .and(warp::path!("api" / "prove"))
-        .and(warp::body::json())
-        .and_then(move |request: ChallengedProof| {
-            let kp = KeyPair::from(ed25519_dalek::Keypair {
-                public: ed25519_dalek::PublicKey::from_bytes(
-                    hex::decode(&app.verify_key).unwrap().as_slice(),
-                )
-                .unwrap(),
-                secret: ed25519_dalek::SecretKey::from_bytes(
-                    hex::decode(&app.sign_key).unwrap().as_slice(),
-                )
-                .unwrap(),
-            });
-            handle_provide_proof(
-                client.clone(),
-                prove_state.clone(),
-                statement.clone(),
-                request,
-                kp,
-            )
-        });
-
     info!(
         "Starting up HTTP server. Listening on port {}.",
         app.port
     );
 
-    tokio::spawn(handle_clean_state(state.clone()));
+    let (shutdown_sender, shutdown) = tokio::sync::watch::channel(());
+    let cleaner = tokio::spawn(handle_clean_state(state.clone(), shutdown));
+
+    let server = routes(state.clone(), node, Arc::new(keys));
+    let (_, server) =
+        warp::serve(server).bind_with_graceful_shutdown(([0, 0, 0, 0], app.port), async {
+            tokio::signal::ctrl_c().await.ok();
+            info!("Shutting down.");
+        });
+    server.await;
 
-    let server = get_challenge
-        .or(get_statement)
-        .or(provide_proof)
-        .recover(handle_rejection)
-        .with(cors)
-        .with(warp::trace::request());
-    warp::serve(server).run(([0, 0, 0, 0], app.port)).await;
+    // Stop the cleanup task and wait for it to finish.
+    shutdown_sender.send(()).ok();
+    cleaner.await?;
+    state
+        .challenges
+        .flush()
+        .context("Unable to write the challenges to disk.")?;
     Ok(())
 }
\ No newline at end of file
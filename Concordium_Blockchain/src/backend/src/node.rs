@@ -0,0 +1,138 @@
+use anyhow::Context;
+use concordium_rust_sdk::{
+    endpoints::QueryError,
+    id::{
+        constants::{ArCurve, AttributeKind},
+        types::{AccountAddress, AccountCredentialWithoutProofs, GlobalContext},
+    },
+    v2::{self, BlockIdentifier},
+};
+use std::{collections::HashMap, fs, path::Path};
+
+pub type Credential = AccountCredentialWithoutProofs<ArCurve, AttributeKind>;
+
+/// The node queries the server makes. Always answered for the last finalized
+/// block.
+#[async_trait::async_trait]
+pub trait NodeBackend: Send + Sync {
+    async fn global_context(&self) -> Result<GlobalContext<ArCurve>, QueryError>;
+
+    /// The credentials currently on the account.
+    async fn account_credentials(
+        &self,
+        address: &AccountAddress,
+    ) -> Result<Vec<Credential>, QueryError>;
+}
+
+/// Queries a node over its gRPC V2 interface.
+pub struct GrpcNode {
+    client: v2::Client,
+}
+
+impl GrpcNode {
+    pub async fn connect(endpoint: v2::Endpoint) -> anyhow::Result<Self> {
+        Ok(Self {
+            client: v2::Client::new(endpoint).await?,
+        })
+    }
+}
+
+#[async_trait::async_trait]
+impl NodeBackend for GrpcNode {
+    async fn global_context(&self) -> Result<GlobalContext<ArCurve>, QueryError> {
+        let response = self
+            .client
+            .clone()
+            .get_cryptographic_parameters(BlockIdentifier::LastFinal)
+            .await?;
+        Ok(response.response)
+    }
+
+    async fn account_credentials(
+        &self,
+        address: &AccountAddress,
+    ) -> Result<Vec<Credential>, QueryError> {
+        let response = self
+            .client
+            .clone()
+            .get_account_info(&(*address).into(), BlockIdentifier::LastFinal)
+            .await?;
+        Ok(response
+            .response
+            .account_credentials
+            .into_values()
+            .map(|credential| credential.value)
+            .collect())
+    }
+}
+
+/// An account in the fixture files of a [`MockNode`].
+#[derive(serde::Deserialize)]
+struct MockAccount {
+    address: AccountAddress,
+    /// In the JSON format the node uses for account credentials.
+    credentials: Vec<serde_json::Value>,
+}
+
+/// Answers queries from fixture files instead of a node, so the server can run
+/// offline. The directory contains
+///
+/// - `cryptographic-parameters.json`, the global context, and
+/// - `accounts.json`, a list of `{ "address": ..., "credentials": [...] }`.
+pub struct MockNode {
+    global_context: serde_json::Value,
+    accounts: HashMap<AccountAddress, Vec<serde_json::Value>>,
+}
+
+impl MockNode {
+    pub fn load(dir: &Path) -> anyhow::Result<Self> {
+        let read = |name: &str| -> anyhow::Result<serde_json::Value> {
+            let path = dir.join(name);
+            let bytes =
+                fs::read(&path).with_context(|| format!("Unable to read {}.", path.display()))?;
+            serde_json::from_slice(&bytes)
+                .with_context(|| format!("Unable to parse {}.", path.display()))
+        };
+        let global_context = read("cryptographic-parameters.json")?;
+        let accounts: Vec<MockAccount> = serde_json::from_value(read("accounts.json")?)
+            .context("Unable to parse the mock accounts.")?;
+        let node = Self {
+            global_context,
+            accounts: accounts
+                .into_iter()
+                .map(|account| (account.address, account.credentials))
+                .collect(),
+        };
+        // Parse everything once, so that broken fixtures fail at startup.
+        serde_json::from_value::<GlobalContext<ArCurve>>(node.global_context.clone())
+            .context("Invalid mock cryptographic parameters.")?;
+        for (address, credentials) in &node.accounts {
+            for credential in credentials {
+                serde_json::from_value::<Credential>(credential.clone())
+                    .with_context(|| format!("Invalid mock credential on {}.", address))?;
+            }
+        }
+        Ok(node)
+    }
+}
+
+// The fixtures were validated when they were loaded, so parse errors here are
+// unexpected. They are reported as missing objects.
+#[async_trait::async_trait]
+impl NodeBackend for MockNode {
+    async fn global_context(&self) -> Result<GlobalContext<ArCurve>, QueryError> {
+        serde_json::from_value(self.global_context.clone()).map_err(|_| QueryError::NotFound)
+    }
+
+    async fn account_credentials(
+        &self,
+        address: &AccountAddress,
+    ) -> Result<Vec<Credential>, QueryError> {
+        let credentials = self.accounts.get(address).ok_or(QueryError::NotFound)?;
+        credentials
+            .iter()
+            .map(|credential| serde_json::from_value(credential.clone()))
+            .collect::<Result<_, _>>()
+            .map_err(|_| QueryError::NotFound)
+    }
+}
@@ -0,0 +1,128 @@
+use anyhow::Context;
+use concordium_rust_sdk::id::{
+    constants::{ArCurve, AttributeKind},
+    id_proof_types::{AtomicStatement, Statement},
+};
+use std::{collections::BTreeMap, fs, path::Path};
+
+/// The policies the server accepts proofs for, by name.
+pub type Policies = BTreeMap<String, Policy>;
+
+/// What a policy asks the user to prove.
+#[derive(serde::Deserialize, Debug, Clone)]
+#[serde(untagged)]
+pub enum Policy {
+    /// A statement that is used as is.
+    Fixed(Statement<ArCurve, AttributeKind>),
+    /// An age requirement, e.g. `{ "minAge": 18 }`, optionally next to a fixed
+    /// statement, e.g. `{ "minAge": 18, "statement": [...] }`. The date of birth
+    /// range depends on the current date, so it is computed for each challenge.
+    Age(AgePolicy),
+}
+
+/// Ages in whole years, both bounds inclusive.
+#[derive(serde::Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct AgePolicy {
+    pub min_age: Option<u64>,
+    pub max_age: Option<u64>,
+    /// Atomic statements that are proven along with the age, e.g. the
+    /// country of residence. The age range is appended to them.
+    #[serde(default)]
+    pub statement: Statement<ArCurve, AttributeKind>,
+}
+
+impl Policy {
+    /// The statement for a challenge issued now. Returns `None` if the ages
+    /// cannot be turned into dates, e.g. because they are larger than the
+    /// current year.
+    pub fn statement(&self) -> Option<Statement<ArCurve, AttributeKind>> {
+        match self {
+            Policy::Fixed(statement) => Some(statement.clone()),
+            Policy::Age(AgePolicy {
+                min_age,
+                max_age,
+                statement,
+            }) => {
+                let statement = statement.clone();
+                // Being at most `max` years old is being younger than `max + 1`.
+                match (*min_age, *max_age) {
+                    (Some(min), Some(max)) if min <= max => {
+                        statement.age_in_range(min, max.checked_add(1)?)
+                    }
+                    (Some(min), None) => statement.older_than(min),
+                    (None, Some(max)) => statement.younger_than(max.checked_add(1)?),
+                    _ => None,
+                }
+            }
+        }
+    }
+
+    /// Whether proofs of the policy are bound to the challenge they answer.
+    /// Range proofs are made with a transcript of their own, so a statement
+    /// of nothing but ranges could be proven once and replayed for any
+    /// challenge. Every other kind of atomic statement is proven with the
+    /// challenge in the transcript.
+    pub fn binds_challenge(&self) -> bool {
+        let statement = match self {
+            Policy::Fixed(statement) => statement,
+            Policy::Age(AgePolicy { statement, .. }) => statement,
+        };
+        statement
+            .statements
+            .iter()
+            .any(|atomic| !matches!(atomic, AtomicStatement::AttributeInRange { .. }))
+    }
+}
+
+/// Loads every `<name>.json` file in `dir` as the policy `<name>`.
+pub fn load_policies(dir: &Path) -> anyhow::Result<Policies> {
+    let mut policies = Policies::new();
+    let entries = fs::read_dir(dir)
+        .with_context(|| format!("Unable to read the policy directory {}.", dir.display()))?;
+    for entry in entries {
+        let path = entry?.path();
+        if path.extension() != Some("json".as_ref()) {
+            continue;
+        }
+        let name = path
+            .file_stem()
+            .and_then(|name| name.to_str())
+            .filter(|name| is_valid_name(name))
+            .with_context(|| format!("Invalid policy name {}.", path.display()))?;
+        let policy = load_policy(&path)?;
+        anyhow::ensure!(
+            policy.binds_challenge(),
+            "The policy {} only proves ranges, which are not bound to the challenge. Add a \
+             RevealAttribute, AttributeInSet or AttributeNotInSet statement.",
+            path.display()
+        );
+        policies.insert(name.to_string(), policy);
+    }
+    anyhow::ensure!(
+        !policies.is_empty(),
+        "No policies found in {}.",
+        dir.display()
+    );
+    Ok(policies)
+}
+
+/// Loads a single policy file.
+pub fn load_policy(path: &Path) -> anyhow::Result<Policy> {
+    let policy: Policy = serde_json::from_slice(&fs::read(path)?)
+        .with_context(|| format!("Unable to parse the policy {}.", path.display()))?;
+    anyhow::ensure!(
+        policy.statement().is_some(),
+        "The policy {} does not describe a valid statement.",
+        path.display()
+    );
+    Ok(policy)
+}
+
+/// Policy names are part of the API paths, so they are kept URL safe.
+fn is_valid_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || b == b'-' || b == b'_')
+}
@@ -0,0 +1,210 @@
+use crate::types::{Challenge, ChallengeStatus};
+use concordium_rust_sdk::common::base16_encode_string;
+use std::{
+    collections::HashMap,
+    sync::{Mutex, MutexGuard},
+    time::Duration,
+};
+
+#[derive(Debug, thiserror::Error)]
+pub enum StoreError {
+    #[error("Error acquiring internal lock.")]
+    Locking,
+    #[cfg(feature = "sled")]
+    #[error("Database error: {0}")]
+    Database(#[from] sled::Error),
+    #[error("Malformed stored challenge: {0}")]
+    Encoding(#[from] serde_json::Error),
+}
+
+/// Where issued challenges are kept until they are used or expire.
+pub trait ChallengeStore: Send + Sync {
+    fn insert(&self, challenge: &Challenge, status: ChallengeStatus) -> Result<(), StoreError>;
+
+    /// The status of the challenge, without consuming it.
+    fn get(&self, challenge: &Challenge) -> Result<Option<ChallengeStatus>, StoreError>;
+
+    /// Removes the challenge and returns its status. Of several concurrent
+    /// calls for the same challenge only one gets `Some`, so a challenge
+    /// can be consumed at most once.
+    fn take(&self, challenge: &Challenge) -> Result<Option<ChallengeStatus>, StoreError>;
+
+    /// Removes the challenges issued more than `expiry` ago and returns how
+    /// many there were.
+    fn remove_expired(&self, expiry: Duration) -> Result<usize, StoreError>;
+
+    /// Writes changes that are still pending to disk. Called once on
+    /// shutdown.
+    fn flush(&self) -> Result<(), StoreError> {
+        Ok(())
+    }
+}
+
+fn key(challenge: &Challenge) -> String {
+    base16_encode_string(&challenge.0)
+}
+
+/// Keeps the challenges in memory, so they are lost on restart.
+#[derive(Default)]
+pub struct MemoryStore {
+    challenges: Mutex<HashMap<String, ChallengeStatus>>,
+}
+
+impl MemoryStore {
+    fn lock(&self) -> Result<MutexGuard<'_, HashMap<String, ChallengeStatus>>, StoreError> {
+        self.challenges.lock().map_err(|_| StoreError::Locking)
+    }
+}
+
+impl ChallengeStore for MemoryStore {
+    fn insert(&self, challenge: &Challenge, status: ChallengeStatus) -> Result<(), StoreError> {
+        self.lock()?.insert(key(challenge), status);
+        Ok(())
+    }
+
+    fn get(&self, challenge: &Challenge) -> Result<Option<ChallengeStatus>, StoreError> {
+        Ok(self.lock()?.get(&key(challenge)).cloned())
+    }
+
+    fn take(&self, challenge: &Challenge) -> Result<Option<ChallengeStatus>, StoreError> {
+        Ok(self.lock()?.remove(&key(challenge)))
+    }
+
+    fn remove_expired(&self, expiry: Duration) -> Result<usize, StoreError> {
+        let mut challenges = self.lock()?;
+        let before = challenges.len();
+        challenges.retain(|_, status| !status.is_expired(expiry));
+        Ok(before - challenges.len())
+    }
+}
+
+/// Keeps the challenges in a sled database on disk, so that sessions survive
+/// a restart of the server. Changes are not flushed on every call, which
+/// would block the async handlers on disk I/O. sled flushes in the
+/// background every half second instead, and the rest is flushed on shutdown.
+#[cfg(feature = "sled")]
+pub struct SledStore {
+    db: sled::Db,
+}
+
+#[cfg(feature = "sled")]
+impl SledStore {
+    pub fn open(path: &std::path::Path) -> Result<Self, StoreError> {
+        Ok(Self {
+            db: sled::open(path)?,
+        })
+    }
+}
+
+#[cfg(feature = "sled")]
+impl ChallengeStore for SledStore {
+    fn insert(&self, challenge: &Challenge, status: ChallengeStatus) -> Result<(), StoreError> {
+        self.db
+            .insert(key(challenge), serde_json::to_vec(&status)?)?;
+        Ok(())
+    }
+
+    fn get(&self, challenge: &Challenge) -> Result<Option<ChallengeStatus>, StoreError> {
+        match self.db.get(key(challenge))? {
+            Some(bytes) => Ok(Some(serde_json::from_slice(&bytes)?)),
+            None => Ok(None),
+        }
+    }
+
+    fn take(&self, challenge: &Challenge) -> Result<Option<ChallengeStatus>, StoreError> {
+        // sled removals are atomic, so only one caller gets the old value.
+        match self.db.remove(key(challenge))? {
+            Some(bytes) => Ok(Some(serde_json::from_slice(&bytes)?)),
+            None => Ok(None),
+        }
+    }
+
+    fn remove_expired(&self, expiry: Duration) -> Result<usize, StoreError> {
+        let mut removed = 0;
+        for entry in self.db.iter() {
+            let (key, bytes) = entry?;
+            // Entries that cannot be read can never be used, so they go too.
+            let expired = serde_json::from_slice::<ChallengeStatus>(&bytes)
+                .map_or(true, |status| status.is_expired(expiry));
+            // Only remove the entry if it was not replaced in the meantime.
+            if expired
+                && self
+                    .db
+                    .compare_and_swap(&key, Some(&bytes), None as Option<&[u8]>)?
+                    .is_ok()
+            {
+                removed += 1;
+            }
+        }
+        Ok(removed)
+    }
+
+    fn flush(&self) -> Result<(), StoreError> {
+        self.db.flush()?;
+        Ok(())
+    }
+}
+
+#[cfg(all(test, feature = "sled"))]
+mod tests {
+    use super::*;
+    use concordium_rust_sdk::id::types::AccountAddress;
+    use std::time::SystemTime;
+
+    fn status(age: Duration) -> ChallengeStatus {
+        ChallengeStatus {
+            address: AccountAddress([1; 32]),
+            created_at: SystemTime::now() - age,
+            policy: "age18".into(),
+            statement: serde_json::from_str("[]").unwrap(),
+        }
+    }
+
+    #[test]
+    fn sled_store_round_trip() {
+        let store = SledStore {
+            db: sled::Config::new().temporary(true).open().unwrap(),
+        };
+        let fresh = Challenge([1; 32]);
+        let old = Challenge([2; 32]);
+        store.insert(&fresh, status(Duration::ZERO)).unwrap();
+        store
+            .insert(&old, status(Duration::from_secs(7200)))
+            .unwrap();
+
+        // Looking a challenge up does not consume it.
+        let stored = store.get(&fresh).unwrap().unwrap();
+        assert_eq!(stored.address, AccountAddress([1; 32]));
+        assert_eq!(stored.policy, "age18");
+        assert!(store.get(&fresh).unwrap().is_some());
+
+        // Taking it does, exactly once.
+        assert!(store.take(&fresh).unwrap().is_some());
+        assert!(store.take(&fresh).unwrap().is_none());
+        assert!(store.get(&fresh).unwrap().is_none());
+
+        store.insert(&fresh, status(Duration::ZERO)).unwrap();
+        assert_eq!(store.remove_expired(Duration::from_secs(3600)).unwrap(), 1);
+        assert!(store.get(&old).unwrap().is_none());
+        assert!(store.get(&fresh).unwrap().is_some());
+    }
+
+    #[test]
+    fn sled_store_survives_a_restart() {
+        let dir = std::env::temp_dir().join(format!(
+            "id-verifier-store-{}",
+            hex::encode(rand::random::<[u8; 8]>())
+        ));
+        let challenge = Challenge([1; 32]);
+        {
+            let store = SledStore::open(&dir).unwrap();
+            store.insert(&challenge, status(Duration::ZERO)).unwrap();
+            store.flush().unwrap();
+        }
+        let store = SledStore::open(&dir).unwrap();
+        let stored = store.get(&challenge).unwrap();
+        drop(store);
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(stored.unwrap().policy, "age18");
+    }
+}
@@ -0,0 +1,134 @@
+use crate::node::Credential;
+use crate::types::{Challenge, ProofWithContext, VersionedProof};
+use concordium_base::{
+    base::CredentialRegistrationID,
+    common::{
+        base16_decode, base16_encode,
+        types::{KeyIndex, KeyPair},
+    },
+    curve_arithmetic::Curve,
+    id::{
+        constants::{ArCurve, AttributeKind},
+        id_proof_types::{Statement, StatementWithContext},
+        secret_sharing::Threshold,
+        types::*,
+    },
+    pedersen_commitment::{Commitment, Randomness, Value},
+};
+use std::collections::BTreeMap;
+
+/// A synthetic identity with a single credential that commits to the given
+/// attributes, for proving statements without a wallet. The credential is
+/// not signed by any identity provider, so it is only accepted by a mock node.
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TestIdentity {
+    pub address: AccountAddress,
+    #[serde(serialize_with = "base16_encode", deserialize_with = "base16_decode")]
+    pub cred_id: ArCurve,
+    pub attributes: AttributeList<<ArCurve as Curve>::Scalar, AttributeKind>,
+    /// The randomness of the attribute commitments. Secret for a real identity.
+    pub randomness: BTreeMap<AttributeTag, Randomness<ArCurve>>,
+    /// The credential as it appears on the account.
+    pub credential: Credential,
+}
+
+#[derive(Debug, thiserror::Error)]
+#[error("No commitment randomness for attribute {0}.")]
+pub struct MissingRandomness(AttributeTag);
+
+impl HasAttributeRandomness<ArCurve> for TestIdentity {
+    type ErrorType = MissingRandomness;
+
+    fn get_attribute_commitment_randomness(
+        &self,
+        attribute_tag: AttributeTag,
+    ) -> Result<Randomness<ArCurve>, Self::ErrorType> {
+        self.randomness
+            .get(&attribute_tag)
+            .cloned()
+            .ok_or(MissingRandomness(attribute_tag))
+    }
+}
+
+impl TestIdentity {
+    pub fn generate(
+        global: &GlobalContext<ArCurve>,
+        alist: BTreeMap<AttributeTag, AttributeKind>,
+    ) -> Self {
+        let mut rng = rand_07::thread_rng();
+        let mut randomness = BTreeMap::new();
+        let mut cmm_attributes = BTreeMap::new();
+        for (tag, value) in &alist {
+            let value = Value::<ArCurve>::new(value.to_field_element());
+            let (commitment, r) = global.on_chain_commitment_key.commit(&value, &mut rng);
+            cmm_attributes.insert(*tag, commitment);
+            randomness.insert(*tag, r);
+        }
+        let valid_to = YearMonth::new(2100, 1).expect("Valid month.");
+        let created_at = YearMonth::now();
+        let cred_id = ArCurve::generate(&mut rng);
+        // Only the attribute commitments are used when verifying statements,
+        // so the other values are random.
+        let credential = Credential::Normal {
+            cdv: CredentialDeploymentValues {
+                cred_key_info: CredentialPublicKeys {
+                    keys: BTreeMap::from([(
+                        KeyIndex(0),
+                        VerifyKey::from(KeyPair::generate(&mut rng).public),
+                    )]),
+                    threshold: SignatureThreshold(1),
+                },
+                cred_id,
+                ip_identity: IpIdentity(0),
+                threshold: Threshold(1),
+                ar_data: BTreeMap::new(),
+                policy: Policy {
+                    valid_to,
+                    created_at,
+                    policy_vec: BTreeMap::new(),
+                    _phantom: Default::default(),
+                },
+            },
+            commitments: CredentialDeploymentCommitments {
+                cmm_prf: Commitment(ArCurve::generate(&mut rng)),
+                cmm_cred_counter: Commitment(ArCurve::generate(&mut rng)),
+                cmm_max_accounts: Commitment(ArCurve::generate(&mut rng)),
+                cmm_attributes,
+                cmm_id_cred_sec_sharing_coeff: vec![Commitment(ArCurve::generate(&mut rng))],
+            },
+        };
+        Self {
+            address: account_address_from_registration_id(&cred_id),
+            cred_id,
+            attributes: AttributeList {
+                valid_to,
+                created_at,
+                max_accounts: 1,
+                alist,
+                _phantom: Default::default(),
+            },
+            randomness,
+            credential,
+        }
+    }
+
+    /// Proves the statement for the challenge. Returns `None` if the
+    /// attributes do not satisfy the statement.
+    pub fn prove(
+        &self,
+        global: &GlobalContext<ArCurve>,
+        statement: &Statement<ArCurve, AttributeKind>,
+        challenge: &Challenge,
+    ) -> Option<ProofWithContext> {
+        let proof = StatementWithContext {
+            credential: self.cred_id,
+            statement: statement.clone(),
+        }
+        .prove(global, &challenge.0, &self.attributes, self)?;
+        Some(ProofWithContext {
+            credential: CredentialRegistrationID::new(self.cred_id),
+            proof: VersionedProof::v0(&proof),
+        })
+    }
+}
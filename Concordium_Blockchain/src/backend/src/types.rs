@@ -0,0 +1,268 @@
+use crate::policy::Policies;
+use crate::store::{ChallengeStore, StoreError};
+use concordium_rust_sdk::{
+    common::{SerdeBase16Serialize, Serialize},
+    endpoints::QueryError,
+    id::{
+        constants::{ArCurve, AttributeKind},
+        id_proof_types::{Proof, Statement},
+        types::{AccountAddress, GlobalContext},
+    },
+    types::{ContractAddress, CredentialRegistrationID},
+};
+use std::{
+    sync::Arc,
+    time::{Duration, SystemTime},
+};
+use warp::http::StatusCode;
+
+/// Decodes the body of a proof of one version into the proof the server verifies.
+pub type ProofDecoder = fn(serde_json::Value) -> Option<Proof<ArCurve, AttributeKind>>;
+
+/// Versions of the [`Proof`] format that the server verifies, each with the
+/// decoder for its body. Proofs in a new wallet format get a new version, which
+/// is only accepted once it is added here together with its decoder.
+pub const ACCEPTED_PROOF_VERSIONS: &[(u32, ProofDecoder)] = &[(0, decode_proof_v0)];
+
+fn decode_proof_v0(value: serde_json::Value) -> Option<Proof<ArCurve, AttributeKind>> {
+    serde_json::from_value(value).ok()
+}
+
+#[derive(
+    Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, SerdeBase16Serialize, Serialize,
+)]
+pub struct Challenge(pub [u8; 32]);
+
+#[derive(serde::Deserialize, Debug, Clone)]
+pub struct WithAccountAddress {
+    pub address: AccountAddress,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Clone)]
+pub struct ChallengeStatus {
+    pub address: AccountAddress,
+    pub created_at: SystemTime,
+    /// The policy the challenge was issued for.
+    pub policy: String,
+    /// The statement of the policy at the time the challenge was issued. The
+    /// proof is verified against this, even if the policy would give another
+    /// statement by now.
+    pub statement: Statement<ArCurve, AttributeKind>,
+}
+
+impl ChallengeStatus {
+    /// Whether the challenge was issued more than `expiry` ago. A creation
+    /// time in the future, e.g. after the clock was set back, does not count
+    /// as expired.
+    pub fn is_expired(&self, expiry: Duration) -> bool {
+        self.created_at
+            .elapsed()
+            .is_ok_and(|elapsed| elapsed >= expiry)
+    }
+}
+
+#[derive(Clone)]
+pub struct Server {
+    pub challenges: Arc<dyn ChallengeStore>,
+    pub global_context: Arc<GlobalContext<ArCurve>>,
+    pub policies: Arc<Policies>,
+    /// The policy of the API paths without a policy name.
+    pub default_policy: String,
+    /// How long a challenge can be used after it was issued.
+    pub challenge_expiry: Duration,
+}
+
+#[derive(Debug)]
+/// An internal error type used by this server to manage error handling.
+#[derive(thiserror::Error)]
+pub enum InjectStatementError {
+    #[error("Not allowed")]
+    NotAllowed,
+    #[error("Invalid proof")]
+    InvalidProofs,
+    #[error("Node access error: {0}")]
+    NodeAccess(Box<QueryError>),
+    #[error("Challenge store error: {0}")]
+    Store(#[from] StoreError),
+    #[error("Proof provided for an unknown session.")]
+    UnknownSession,
+    #[error("Proof provided for an expired challenge.")]
+    ExpiredChallenge,
+    #[error("The credential the proof was made with is not on the account.")]
+    CredentialNotOnAccount,
+    #[error("Proof version {0} is not supported.")]
+    UnsupportedProofVersion(u32),
+    #[error("Unknown policy.")]
+    UnknownPolicy,
+    #[error("The challenge was issued for another policy.")]
+    WrongPolicy,
+    #[error("The policy does not describe a valid statement.")]
+    InvalidPolicy,
+    #[error("None of the signing keys is currently valid.")]
+    NoSigningKey,
+}
+
+impl warp::reject::Reject for InjectStatementError {}
+
+// Boxed, since node errors are much larger than the other variants.
+impl From<QueryError> for InjectStatementError {
+    fn from(e: QueryError) -> Self {
+        InjectStatementError::NodeAccess(Box::new(e))
+    }
+}
+
+impl InjectStatementError {
+    /// The HTTP status the error is reported with.
+    pub fn status_code(&self) -> StatusCode {
+        match self {
+            InjectStatementError::NotAllowed => StatusCode::BAD_REQUEST,
+            InjectStatementError::InvalidProofs => StatusCode::BAD_REQUEST,
+            InjectStatementError::NodeAccess(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            InjectStatementError::Store(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            InjectStatementError::UnknownSession => StatusCode::NOT_FOUND,
+            InjectStatementError::ExpiredChallenge => StatusCode::GONE,
+            InjectStatementError::CredentialNotOnAccount => StatusCode::BAD_REQUEST,
+            InjectStatementError::UnsupportedProofVersion(_) => StatusCode::BAD_REQUEST,
+            InjectStatementError::UnknownPolicy => StatusCode::NOT_FOUND,
+            InjectStatementError::WrongPolicy => StatusCode::BAD_REQUEST,
+            InjectStatementError::InvalidPolicy => StatusCode::INTERNAL_SERVER_ERROR,
+            InjectStatementError::NoSigningKey => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    /// Stable code identifying the error in responses, so that clients can
+    /// tell errors apart without parsing the message.
+    pub fn error_code(&self) -> &'static str {
+        match self {
+            InjectStatementError::NotAllowed => "NOT_ALLOWED",
+            InjectStatementError::InvalidProofs => "INVALID_PROOF",
+            InjectStatementError::NodeAccess(_) => "NODE_UNAVAILABLE",
+            InjectStatementError::Store(_) => "INTERNAL_ERROR",
+            InjectStatementError::UnknownSession => "UNKNOWN_SESSION",
+            InjectStatementError::ExpiredChallenge => "EXPIRED_CHALLENGE",
+            InjectStatementError::CredentialNotOnAccount => "CREDENTIAL_NOT_ON_ACCOUNT",
+            InjectStatementError::UnsupportedProofVersion(_) => "UNSUPPORTED_PROOF_VERSION",
+            InjectStatementError::UnknownPolicy => "UNKNOWN_POLICY",
+            InjectStatementError::WrongPolicy => "WRONG_POLICY",
+            InjectStatementError::InvalidPolicy => "INVALID_POLICY",
+            InjectStatementError::NoSigningKey => "NO_SIGNING_KEY",
+        }
+    }
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug)]
+/// Response in case of an error. This is going to be encoded as a JSON body
+/// with fields 'code' and 'message'.
+pub struct ErrorResponse {
+    /// One of the codes listed by [`InjectStatementError::error_code`], or a
+    /// code for a request that could not be parsed, e.g. `MALFORMED_REQUEST`.
+    pub code: String,
+    pub message: String,
+}
+
+#[derive(serde::Deserialize, serde::Serialize, Debug)]
+pub struct ChallengeResponse {
+    pub challenge: Challenge,
+    /// The statement to prove for this challenge.
+    pub statement: Statement<ArCurve, AttributeKind>,
+}
+
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone)]
+pub struct ChallengedProof {
+    pub challenge: Challenge,
+    pub proof: ProofWithContext,
+    /// The contract the resulting attestation is meant for, if any.
+    #[serde(default)]
+    pub contract: Option<ContractAddress>,
+}
+
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone)]
+pub struct ProofWithContext {
+    pub credential: CredentialRegistrationID,
+    pub proof: VersionedProof,
+}
+
+/// A proof as sent by the wallet. The body is kept as JSON until the version
+/// is known, since proofs of different versions need not share a format.
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone)]
+pub struct VersionedProof {
+    pub v: u32,
+    pub value: serde_json::Value,
+}
+
+impl VersionedProof {
+    /// Wraps a proof in the version 0 format.
+    pub fn v0(proof: &Proof<ArCurve, AttributeKind>) -> Self {
+        VersionedProof {
+            v: 0,
+            value: serde_json::to_value(proof).expect("Proofs serialize to JSON."),
+        }
+    }
+}
+
+impl ProofWithContext {
+    /// The proof, decoded by the decoder for its version in
+    /// [`ACCEPTED_PROOF_VERSIONS`].
+    pub fn versioned_proof(&self) -> Result<Proof<ArCurve, AttributeKind>, InjectStatementError> {
+        let version = self.proof.v;
+        let (_, decode) = ACCEPTED_PROOF_VERSIONS
+            .iter()
+            .find(|(accepted, _)| *accepted == version)
+            .ok_or(InjectStatementError::UnsupportedProofVersion(version))?;
+        decode(self.proof.value.clone()).ok_or(InjectStatementError::InvalidProofs)
+    }
+}
+
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+/// The statement that the server signs once a proof has been verified.
+pub struct Attestation {
+    pub account: AccountAddress,
+    /// SHA-256 of the name of the policy that was proven. Unlike the statement
+    /// of an age policy it stays the same from day to day, so that contracts
+    /// can fix it once.
+    #[serde(with = "hex")]
+    pub policy_hash: [u8; 32],
+    pub challenge: Challenge,
+    /// Unix time in milliseconds.
+    pub issued_at: u64,
+    /// Unix time in milliseconds after which the attestation must be rejected.
+    pub expires_at: u64,
+    pub contract: Option<ContractAddress>,
+    /// Id of the key in `/api/keys` that signs the attestation.
+    pub key_id: String,
+}
+
+impl Attestation {
+    /// The bytes that are signed. This is the smart contract serialization of
+    /// the attestation (little-endian integers, `Option` tagged with a byte,
+    /// strings prefixed with their `u32` length), so that contracts can check
+    /// the signature against `to_bytes` of the same structure.
+    pub fn signed_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(3 * 32 + 2 * 8 + 17 + 4 + self.key_id.len());
+        out.extend_from_slice(&self.account.0);
+        out.extend_from_slice(&self.policy_hash);
+        out.extend_from_slice(&self.challenge.0);
+        out.extend_from_slice(&self.issued_at.to_le_bytes());
+        out.extend_from_slice(&self.expires_at.to_le_bytes());
+        match self.contract {
+            None => out.push(0),
+            Some(contract) => {
+                out.push(1);
+                out.extend_from_slice(&contract.index.to_le_bytes());
+                out.extend_from_slice(&contract.subindex.to_le_bytes());
+            }
+        }
+        out.extend_from_slice(&(self.key_id.len() as u32).to_le_bytes());
+        out.extend_from_slice(self.key_id.as_bytes());
+        out
+    }
+}
+
+#[derive(serde::Deserialize, serde::Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ProofResponse {
+    pub attestation: Attestation,
+    /// Hex encoded ed25519 signature of `attestation.signed_bytes()`.
+    pub signature: String,
+}
@@ -0,0 +1,72 @@
+use concordium_base::id::{
+    constants::{ArCurve, AttributeKind},
+    id_proof_types::{AtomicStatement, Proof, Statement},
+    types::{AttributeTag, CredentialDeploymentCommitments, GlobalContext},
+};
+
+/// Why a proof does not verify against a statement.
+#[derive(Debug, thiserror::Error)]
+pub enum VerificationError {
+    #[error("The statement has {statements} parts, but the proof has {proofs}.")]
+    LengthMismatch { statements: usize, proofs: usize },
+    #[error(
+        "Part {index} of the statement is about {tag}, which the credential has no commitment to."
+    )]
+    MissingCommitment { index: usize, tag: AttributeTag },
+    #[error("Part {index} of the statement does not verify: {statement}")]
+    PartFailed { index: usize, statement: String },
+    #[error("The proof does not verify.")]
+    Failed,
+}
+
+/// Verifies the proof like [`Statement::verify`], and on failure finds the
+/// first part of the statement that does not verify.
+pub fn verify_statement(
+    statement: &Statement<ArCurve, AttributeKind>,
+    challenge: &[u8],
+    global: &GlobalContext<ArCurve>,
+    cred_id: &ArCurve,
+    commitments: &CredentialDeploymentCommitments<ArCurve>,
+    proof: &Proof<ArCurve, AttributeKind>,
+) -> Result<(), VerificationError> {
+    if statement.verify(challenge, global, cred_id, commitments, proof) {
+        return Ok(());
+    }
+    if statement.statements.len() != proof.proofs.len() {
+        return Err(VerificationError::LengthMismatch {
+            statements: statement.statements.len(),
+            proofs: proof.proofs.len(),
+        });
+    }
+    // The parts share one transcript, so they cannot be verified on their
+    // own. Verifying ever longer prefixes finds the first failing part.
+    for (index, atomic) in statement.statements.iter().enumerate() {
+        let tag = attribute_tag(atomic);
+        if !commitments.cmm_attributes.contains_key(&tag) {
+            return Err(VerificationError::MissingCommitment { index, tag });
+        }
+        let prefix = Statement {
+            statements: statement.statements[..=index].to_vec(),
+        };
+        let prefix_proof = Proof {
+            proofs: proof.proofs[..=index].to_vec(),
+        };
+        if !prefix.verify(challenge, global, cred_id, commitments, &prefix_proof) {
+            return Err(VerificationError::PartFailed {
+                index,
+                statement: serde_json::to_string(atomic)
+                    .unwrap_or_else(|_| format!("{:?}", atomic)),
+            });
+        }
+    }
+    Err(VerificationError::Failed)
+}
+
+fn attribute_tag(statement: &AtomicStatement<ArCurve, AttributeKind>) -> AttributeTag {
+    match statement {
+        AtomicStatement::RevealAttribute { statement } => statement.attribute_tag,
+        AtomicStatement::AttributeInRange { statement } => statement.attribute_tag,
+        AtomicStatement::AttributeInSet { statement } => statement.attribute_tag,
+        AtomicStatement::AttributeNotInSet { statement } => statement.attribute_tag,
+    }
+}
@@ -0,0 +1,632 @@
+//! Runs the HTTP API in-process against a mock node with a synthetic
+//! identity, so the whole prove flow can be checked without a node or wallet.
+
+use concordium_base::{
+    common::types::KeyPair,
+    id::{
+        constants::{ArCurve, AttributeKind},
+        id_proof_types::{AtomicStatement, Statement},
+        types::{AttributeTag, GlobalContext},
+    },
+};
+use ed25519_dalek::Verifier;
+use id_verifier::{
+    keys::{KeySet, SigningKey},
+    node::{MockNode, NodeBackend},
+    policy::{load_policies, load_policy, AgePolicy, Policies, Policy},
+    routes,
+    store::MemoryStore,
+    test_identity::TestIdentity,
+    types::*,
+};
+use sha2::{Digest, Sha256};
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::PathBuf,
+    sync::Arc,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+use warp::{http::StatusCode, Filter};
+
+/// Someone born on 1 January 1990, living in Denmark and identified by
+/// passport.
+fn test_identity(global: &GlobalContext<ArCurve>) -> TestIdentity {
+    TestIdentity::generate(
+        global,
+        BTreeMap::from([
+            (AttributeTag::from(3u8), AttributeKind("19900101".into())),
+            (AttributeTag::from(4u8), AttributeKind("DK".into())),
+            (AttributeTag::from(6u8), AttributeKind("1".into())),
+        ]),
+    )
+}
+
+/// A policy from the `config` directory that ships with the server.
+fn config_policy(name: &str) -> Policy {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("config")
+        .join(format!("{}.json", name));
+    load_policy(&path).unwrap()
+}
+
+/// Proves a statement the identity satisfies.
+fn prove(
+    identity: &TestIdentity,
+    global: &GlobalContext<ArCurve>,
+    statement: &Statement<ArCurve, AttributeKind>,
+    challenge: &Challenge,
+) -> ProofWithContext {
+    identity
+        .prove(global, statement, challenge)
+        .expect("The identity satisfies the statement.")
+}
+
+struct TestServer {
+    global: GlobalContext<ArCurve>,
+    identity: TestIdentity,
+    state: Server,
+    node: Arc<dyn NodeBackend>,
+    keys: Arc<KeySet>,
+}
+
+/// A key that was rotated out an hour ago, the key that replaced it, and a
+/// key that takes over in an hour.
+fn rotated_keys() -> KeySet {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_millis() as u64;
+    let hour = 3600 * 1000;
+    let key = |id: &str, valid_from, valid_until| SigningKey {
+        id: id.into(),
+        key_pair: KeyPair::generate(&mut rand_07::thread_rng()),
+        valid_from,
+        valid_until,
+    };
+    KeySet::new(vec![
+        key("old", now - 2 * hour, Some(now - hour)),
+        key("current", now - hour, None),
+        key("next", now + hour, None),
+    ])
+    .unwrap()
+}
+
+impl TestServer {
+    /// Writes the identity's account into fixture files in a fresh directory
+    /// and starts the API on a mock node that reads them.
+    async fn start(challenge_expiry: Duration) -> Self {
+        let global = GlobalContext::generate("genesis".into());
+        let identity = test_identity(&global);
+        let dir = fixture_dir();
+        fs::write(
+            dir.join("cryptographic-parameters.json"),
+            serde_json::to_vec(&global).unwrap(),
+        )
+        .unwrap();
+        let accounts = serde_json::json!([{
+            "address": identity.address,
+            "credentials": [identity.credential],
+        }]);
+        fs::write(dir.join("accounts.json"), accounts.to_string()).unwrap();
+        let node: Arc<dyn NodeBackend> = Arc::new(MockNode::load(&dir).unwrap());
+        fs::remove_dir_all(&dir).unwrap();
+
+        let Policy::Age(age18) = config_policy("age18") else {
+            panic!("age18 is an age policy.");
+        };
+        let mut policies = Policies::new();
+        policies.insert(
+            "age21".into(),
+            Policy::Age(AgePolicy {
+                min_age: Some(21),
+                ..age18.clone()
+            }),
+        );
+        policies.insert("age18".into(), Policy::Age(age18));
+        policies.insert("eu-resident".into(), config_policy("eu-resident"));
+        let state = Server {
+            challenges: Arc::new(MemoryStore::default()),
+            global_context: Arc::new(node.global_context().await.unwrap()),
+            policies: Arc::new(policies),
+            default_policy: "age18".into(),
+            challenge_expiry,
+        };
+        Self {
+            global,
+            identity,
+            state,
+            node,
+            keys: Arc::new(rotated_keys()),
+        }
+    }
+
+    fn routes(&self) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> {
+        routes(self.state.clone(), self.node.clone(), self.keys.clone())
+    }
+
+    async fn challenge(&self) -> ChallengeResponse {
+        let response = warp::test::request()
+            .path(&format!(
+                "/api/challenge/age18?address={}",
+                self.identity.address
+            ))
+            .reply(&self.routes())
+            .await;
+        assert_eq!(response.status(), StatusCode::OK);
+        serde_json::from_slice(response.body()).unwrap()
+    }
+
+    async fn keys(&self) -> serde_json::Value {
+        let response = warp::test::request()
+            .path("/api/keys")
+            .reply(&self.routes())
+            .await;
+        assert_eq!(response.status(), StatusCode::OK);
+        serde_json::from_slice(response.body()).unwrap()
+    }
+
+    async fn prove(&self, request: &ChallengedProof) -> (StatusCode, serde_json::Value) {
+        self.reply(
+            warp::test::request()
+                .method("POST")
+                .path("/api/prove/age18")
+                .json(request),
+        )
+        .await
+    }
+
+    async fn reply(&self, request: warp::test::RequestBuilder) -> (StatusCode, serde_json::Value) {
+        let response = request.reply(&self.routes()).await;
+        (
+            response.status(),
+            serde_json::from_slice(response.body()).unwrap(),
+        )
+    }
+}
+
+fn fixture_dir() -> PathBuf {
+    let mut name = [0u8; 8];
+    rand_07::Rng::fill(&mut rand_07::thread_rng(), &mut name);
+    let dir = std::env::temp_dir().join(format!("id-verifier-{}", hex::encode(name)));
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn assert_error(response: (StatusCode, serde_json::Value), status: StatusCode, code: &str) {
+    assert_eq!(response.0, status, "{}", response.1);
+    assert_eq!(response.1["code"], code);
+}
+
+#[tokio::test]
+async fn valid_proof_is_attested() {
+    let server = TestServer::start(Duration::from_secs(600)).await;
+    let ChallengeResponse {
+        challenge,
+        statement,
+    } = server.challenge().await;
+    let request = ChallengedProof {
+        challenge,
+        proof: prove(&server.identity, &server.global, &statement, &challenge),
+        contract: None,
+    };
+
+    let (status, body) = server.prove(&request).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    let response: ProofResponse = serde_json::from_value(body).unwrap();
+    assert_eq!(response.attestation.account, server.identity.address);
+    assert_eq!(response.attestation.challenge, challenge);
+    assert_eq!(response.attestation.key_id, "current");
+    // It names the policy rather than the statement, whose dates change daily.
+    assert_eq!(
+        response.attestation.policy_hash,
+        <[u8; 32]>::from(Sha256::digest(b"age18"))
+    );
+    // The signature checks out with the published key of that id.
+    let keys = server.keys().await;
+    let key = keys["keys"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|key| key["kid"] == response.attestation.key_id)
+        .unwrap();
+    let public = ed25519_dalek::PublicKey::from_bytes(
+        &hex::decode(key["verifyKey"].as_str().unwrap()).unwrap(),
+    )
+    .unwrap();
+    let signature =
+        ed25519_dalek::Signature::from_bytes(&hex::decode(&response.signature).unwrap()).unwrap();
+    assert!(public
+        .verify(&response.attestation.signed_bytes(), &signature)
+        .is_ok());
+
+    // The challenge is used up.
+    assert_error(
+        server.prove(&request).await,
+        StatusCode::NOT_FOUND,
+        "UNKNOWN_SESSION",
+    );
+}
+
+#[tokio::test]
+async fn age_is_proven_along_with_a_fixed_statement() {
+    let server = TestServer::start(Duration::from_secs(600)).await;
+    let (status, body) = server
+        .reply(warp::test::request().path(&format!(
+            "/api/challenge/eu-resident?address={}",
+            server.identity.address
+        )))
+        .await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    let ChallengeResponse {
+        challenge,
+        statement,
+    } = serde_json::from_value(body).unwrap();
+    // The country of residence from the policy file, then today's age range.
+    assert_eq!(statement.statements.len(), 2);
+    assert!(matches!(
+        statement.statements[0],
+        AtomicStatement::AttributeInSet { .. }
+    ));
+    assert_eq!(
+        statement.statements[1..],
+        Statement::new().older_than(18).unwrap().statements[..]
+    );
+
+    let request = ChallengedProof {
+        challenge,
+        proof: prove(&server.identity, &server.global, &statement, &challenge),
+        contract: None,
+    };
+    let (status, body) = server
+        .reply(
+            warp::test::request()
+                .method("POST")
+                .path("/api/prove/eu-resident")
+                .json(&request),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+}
+
+#[tokio::test]
+async fn paths_without_a_policy_use_the_default_policy() {
+    let server = TestServer::start(Duration::from_secs(600)).await;
+    let response = warp::test::request()
+        .path(&format!(
+            "/api/challenge?address={}",
+            server.identity.address
+        ))
+        .reply(&server.routes())
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let ChallengeResponse {
+        challenge,
+        statement,
+    } = serde_json::from_slice(response.body()).unwrap();
+    let response = warp::test::request()
+        .path("/api/statement")
+        .reply(&server.routes())
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let default_statement: serde_json::Value = serde_json::from_slice(response.body()).unwrap();
+    assert_eq!(
+        default_statement,
+        serde_json::to_value(server.state.policies["age18"].statement()).unwrap()
+    );
+
+    let request = ChallengedProof {
+        challenge,
+        proof: prove(&server.identity, &server.global, &statement, &challenge),
+        contract: None,
+    };
+    let response = warp::test::request()
+        .method("POST")
+        .path("/api/prove")
+        .json(&request)
+        .reply(&server.routes())
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+}
+
+#[tokio::test]
+async fn proof_with_another_credential_is_rejected() {
+    let server = TestServer::start(Duration::from_secs(600)).await;
+    let ChallengeResponse {
+        challenge,
+        statement,
+    } = server.challenge().await;
+    let stranger = test_identity(&server.global);
+    let request = ChallengedProof {
+        challenge,
+        proof: prove(&stranger, &server.global, &statement, &challenge),
+        contract: None,
+    };
+
+    assert_error(
+        server.prove(&request).await,
+        StatusCode::BAD_REQUEST,
+        "CREDENTIAL_NOT_ON_ACCOUNT",
+    );
+}
+
+#[tokio::test]
+async fn proof_for_unknown_session_is_rejected() {
+    let server = TestServer::start(Duration::from_secs(600)).await;
+    let ChallengeResponse { statement, .. } = server.challenge().await;
+    let challenge = Challenge([7; 32]);
+    let request = ChallengedProof {
+        challenge,
+        proof: prove(&server.identity, &server.global, &statement, &challenge),
+        contract: None,
+    };
+
+    assert_error(
+        server.prove(&request).await,
+        StatusCode::NOT_FOUND,
+        "UNKNOWN_SESSION",
+    );
+}
+
+#[tokio::test]
+async fn tampered_proof_is_rejected() {
+    let server = TestServer::start(Duration::from_secs(600)).await;
+    let ChallengeResponse {
+        challenge,
+        statement,
+    } = server.challenge().await;
+    // A proof of a statement with other bounds than the one issued.
+    let tampered: Statement<ArCurve, AttributeKind> = serde_json::from_value({
+        let mut json = serde_json::to_value(&statement).unwrap();
+        json[1]["lower"] = "18000102".into();
+        json
+    })
+    .unwrap();
+    let request = ChallengedProof {
+        challenge,
+        proof: prove(&server.identity, &server.global, &tampered, &challenge),
+        contract: None,
+    };
+
+    assert_error(
+        server.prove(&request).await,
+        StatusCode::BAD_REQUEST,
+        "INVALID_PROOF",
+    );
+}
+
+#[tokio::test]
+async fn proof_for_another_challenge_is_rejected() {
+    let server = TestServer::start(Duration::from_secs(600)).await;
+    let first = server.challenge().await;
+    let second = server.challenge().await;
+    // Both challenges ask for the same statement today, but the proof only
+    // answers the challenge it was made for.
+    assert_eq!(first.statement, second.statement);
+    let request = ChallengedProof {
+        challenge: second.challenge,
+        proof: prove(
+            &server.identity,
+            &server.global,
+            &first.statement,
+            &first.challenge,
+        ),
+        contract: None,
+    };
+
+    assert_error(
+        server.prove(&request).await,
+        StatusCode::BAD_REQUEST,
+        "INVALID_PROOF",
+    );
+}
+
+#[tokio::test]
+async fn range_only_policies_are_not_loaded() {
+    let dir = fixture_dir();
+    fs::write(dir.join("age.json"), r#"{ "minAge": 18 }"#).unwrap();
+    let error = load_policies(&dir).unwrap_err();
+    assert!(
+        error.to_string().contains("not bound to the challenge"),
+        "{}",
+        error
+    );
+
+    // The policies that ship with the server are all bound.
+    let config = load_policies(&PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("config"));
+    fs::remove_dir_all(&dir).unwrap();
+    assert_eq!(config.unwrap().len(), 2);
+}
+
+#[tokio::test]
+async fn proof_for_expired_challenge_is_rejected() {
+    let server = TestServer::start(Duration::ZERO).await;
+    let ChallengeResponse {
+        challenge,
+        statement,
+    } = server.challenge().await;
+    let request = ChallengedProof {
+        challenge,
+        proof: prove(&server.identity, &server.global, &statement, &challenge),
+        contract: None,
+    };
+
+    assert_error(
+        server.prove(&request).await,
+        StatusCode::GONE,
+        "EXPIRED_CHALLENGE",
+    );
+}
+
+#[tokio::test]
+async fn proof_of_unsupported_version_is_rejected() {
+    let server = TestServer::start(Duration::from_secs(600)).await;
+    let ChallengeResponse {
+        challenge,
+        statement,
+    } = server.challenge().await;
+    let mut request = ChallengedProof {
+        challenge,
+        proof: prove(&server.identity, &server.global, &statement, &challenge),
+        contract: None,
+    };
+    request.proof.proof.v = 1;
+
+    assert_error(
+        server.prove(&request).await,
+        StatusCode::BAD_REQUEST,
+        "UNSUPPORTED_PROOF_VERSION",
+    );
+}
+
+#[tokio::test]
+async fn proof_in_future_format_is_rejected_by_its_version() {
+    let server = TestServer::start(Duration::from_secs(600)).await;
+    let ChallengeResponse {
+        challenge,
+        statement,
+    } = server.challenge().await;
+    let mut request = ChallengedProof {
+        challenge,
+        proof: prove(&server.identity, &server.global, &statement, &challenge),
+        contract: None,
+    };
+    // A body the version 0 decoder could not read is still reported by its
+    // version rather than as a malformed request.
+    request.proof.proof = VersionedProof {
+        v: 2,
+        value: serde_json::json!({ "format": "future", "proofs": [[1, 2, 3]] }),
+    };
+
+    assert_error(
+        server.prove(&request).await,
+        StatusCode::BAD_REQUEST,
+        "UNSUPPORTED_PROOF_VERSION",
+    );
+}
+
+#[tokio::test]
+async fn proof_for_another_policy_is_rejected() {
+    let server = TestServer::start(Duration::from_secs(600)).await;
+    let ChallengeResponse {
+        challenge,
+        statement,
+    } = server.challenge().await;
+    let request = ChallengedProof {
+        challenge,
+        proof: prove(&server.identity, &server.global, &statement, &challenge),
+        contract: None,
+    };
+
+    assert_error(
+        server
+            .reply(
+                warp::test::request()
+                    .method("POST")
+                    .path("/api/prove/age21")
+                    .json(&request),
+            )
+            .await,
+        StatusCode::BAD_REQUEST,
+        "WRONG_POLICY",
+    );
+}
+
+#[tokio::test]
+async fn unknown_policy_is_rejected() {
+    let server = TestServer::start(Duration::from_secs(600)).await;
+    assert_error(
+        server
+            .reply(warp::test::request().path(&format!(
+                "/api/challenge/age99?address={}",
+                server.identity.address
+            )))
+            .await,
+        StatusCode::NOT_FOUND,
+        "UNKNOWN_POLICY",
+    );
+    assert_error(
+        server
+            .reply(warp::test::request().path("/api/statement/age99"))
+            .await,
+        StatusCode::NOT_FOUND,
+        "UNKNOWN_POLICY",
+    );
+}
+
+#[tokio::test]
+async fn malformed_json_is_rejected() {
+    let server = TestServer::start(Duration::from_secs(600)).await;
+    assert_error(
+        server
+            .reply(
+                warp::test::request()
+                    .method("POST")
+                    .path("/api/prove/age18")
+                    .header("content-type", "application/json")
+                    .body("{\"challenge\": "),
+            )
+            .await,
+        StatusCode::BAD_REQUEST,
+        "MALFORMED_REQUEST",
+    );
+}
+
+#[tokio::test]
+async fn oversized_payload_is_rejected() {
+    let server = TestServer::start(Duration::from_secs(600)).await;
+    assert_error(
+        server
+            .reply(
+                warp::test::request()
+                    .method("POST")
+                    .path("/api/prove/age18")
+                    .header("content-type", "application/json")
+                    .body(vec![b' '; 51 * 1024]),
+            )
+            .await,
+        StatusCode::PAYLOAD_TOO_LARGE,
+        "PAYLOAD_TOO_LARGE",
+    );
+}
+
+#[tokio::test]
+async fn wrong_method_is_rejected() {
+    let server = TestServer::start(Duration::from_secs(600)).await;
+    assert_error(
+        server
+            .reply(warp::test::request().method("GET").path("/api/prove/age18"))
+            .await,
+        StatusCode::METHOD_NOT_ALLOWED,
+        "METHOD_NOT_ALLOWED",
+    );
+}
+
+#[tokio::test]
+async fn keys_are_published_with_their_status() {
+    let server = TestServer::start(Duration::from_secs(600)).await;
+    let keys = server.keys().await;
+    let keys = keys["keys"].as_array().unwrap();
+    let status = |id: &str| {
+        keys.iter()
+            .find(|key| key["kid"] == id)
+            .map(|key| key["status"].clone())
+    };
+    assert_eq!(status("old"), Some("retired".into()));
+    assert_eq!(status("current"), Some("active".into()));
+    assert_eq!(status("next"), Some("pending".into()));
+    for key in keys {
+        let id = key["kid"].as_str().unwrap();
+        let public = server.keys.get(id).unwrap().key_pair.public;
+        assert_eq!(key["kty"], "OKP");
+        assert_eq!(key["crv"], "Ed25519");
+        assert_eq!(
+            base64::Engine::decode(
+                &base64::engine::general_purpose::URL_SAFE_NO_PAD,
+                key["x"].as_str().unwrap()
+            )
+            .unwrap(),
+            public.as_bytes()
+        );
+    }
+}
@@ -1,148 +1,867 @@
-mod cis2_multi {
+pub mod cis2_multi {
+    use concordium_cis2::*;
     use concordium_std::*;
 
-    #[concordium_contract(storage)]
-    pub fn cis2_multi_contract() -> (Storage, Event) {
-        // Implementation of the CIS2 Multi contract
-        unimplemented!()
+    // The standards this contract reports as supported through `supports`.
+    const SUPPORTS_STANDARDS: [StandardIdentifier<'static>; 2] =
+        [CIS0_STANDARD_IDENTIFIER, CIS2_STANDARD_IDENTIFIER];
+
+    pub type ContractTokenId = TokenIdU32;
+
+    pub type ContractTokenAmount = TokenAmountU64;
+
+    pub type ContractResult<A> = Result<A, ContractError>;
+
+    pub type ContractError = Cis2Error<CustomContractError>;
+
+    // Balances and operators of a single address.
+    #[derive(Serial, DeserialWithState, Deletable)]
+    #[concordium(state_parameter = "S")]
+    pub struct AddressState<S> {
+        balances: StateMap<ContractTokenId, ContractTokenAmount, S>,
+        operators: StateSet<Address, S>,
     }
-}
 
-mod cis2_market {
-    use concordium_std::*;
+    impl<S: HasStateApi> AddressState<S> {
+        fn empty(state_builder: &mut StateBuilder<S>) -> Self {
+            AddressState {
+                balances: state_builder.new_map(),
+                operators: state_builder.new_set(),
+            }
+        }
+    }
 
-    #[concordium_contract(storage)]
-    pub fn cis2_market_contract() -> (Storage, Event) {
-        // Implementation of the CIS2 Market contract
-        unimplemented!()
+    #[derive(Serial, DeserialWithState)]
+    #[concordium(state_parameter = "S")]
+    pub struct State<S> {
+        // The account allowed to mint new tokens.
+        owner: AccountAddress,
+        state: StateMap<Address, AddressState<S>, S>,
+        tokens: StateMap<ContractTokenId, MetadataUrl, S>,
+        implementors: StateMap<StandardIdentifierOwned, Vec<ContractAddress>, S>,
     }
-}
 
-#[concordium_contract(storage)]
-pub fn mint_nft(
-    ctx: &impl HasActions,
-    owner: AccountAddress,
-    identity: String,
-    conditions: String,
-) -> (Storage, Event) {
-    let caller = ctx.get_caller();
-
-    // Verify the ownership and identity
-    if caller != owner {
-        return (
-            Storage::new(),
-            Event::new().error("Caller is not the owner of the NFT"),
+    #[derive(Serialize, SchemaType)]
+    pub struct MintParams {
+        pub owner: Address,
+        pub token_id: ContractTokenId,
+        pub amount: ContractTokenAmount,
+        pub metadata_url: MetadataUrl,
+    }
+
+    #[derive(Serialize, SchemaType)]
+    pub struct SetImplementorsParams {
+        pub id: StandardIdentifierOwned,
+        pub implementors: Vec<ContractAddress>,
+    }
+
+    #[derive(Serialize, Debug, PartialEq, Eq, Reject, SchemaType)]
+    pub enum CustomContractError {
+        #[from(ParseError)]
+        ParseParams,
+        LogFull,
+        LogMalformed,
+        InvokeContractError,
+    }
+
+    impl From<LogError> for CustomContractError {
+        fn from(le: LogError) -> Self {
+            match le {
+                LogError::Full => Self::LogFull,
+                LogError::Malformed => Self::LogMalformed,
+            }
+        }
+    }
+
+    impl<T> From<CallContractError<T>> for CustomContractError {
+        fn from(_cce: CallContractError<T>) -> Self {
+            Self::InvokeContractError
+        }
+    }
+
+    impl From<CustomContractError> for ContractError {
+        fn from(c: CustomContractError) -> Self {
+            Cis2Error::Custom(c)
+        }
+    }
+
+    impl<S: HasStateApi> State<S> {
+        fn empty(owner: AccountAddress, state_builder: &mut StateBuilder<S>) -> Self {
+            State {
+                owner,
+                state: state_builder.new_map(),
+                tokens: state_builder.new_map(),
+                implementors: state_builder.new_map(),
+            }
+        }
+
+        // Adds `amount` of `token_id` to `owner`, registering the token on its
+        // first mint. Returns whether the token was new.
+        fn mint(
+            &mut self,
+            token_id: &ContractTokenId,
+            metadata_url: &MetadataUrl,
+            amount: ContractTokenAmount,
+            owner: &Address,
+            state_builder: &mut StateBuilder<S>,
+        ) -> bool {
+            let is_new = self.tokens.get(token_id).is_none();
+            if is_new {
+                let _ = self.tokens.insert(*token_id, metadata_url.clone());
+            }
+            let mut owner_state = self
+                .state
+                .entry(*owner)
+                .or_insert_with(|| AddressState::empty(state_builder));
+            let mut owner_balance = owner_state.balances.entry(*token_id).or_insert(0.into());
+            *owner_balance += amount;
+            is_new
+        }
+
+        fn contains_token(&self, token_id: &ContractTokenId) -> bool {
+            self.tokens.get(token_id).is_some()
+        }
+
+        fn balance(
+            &self,
+            token_id: &ContractTokenId,
+            address: &Address,
+        ) -> ContractResult<ContractTokenAmount> {
+            ensure!(self.contains_token(token_id), ContractError::InvalidTokenId);
+            let balance = self.state.get(address).map_or(0.into(), |address_state| {
+                address_state
+                    .balances
+                    .get(token_id)
+                    .map_or(0.into(), |balance| *balance)
+            });
+            Ok(balance)
+        }
+
+        fn is_operator(&self, address: &Address, owner: &Address) -> bool {
+            self.state
+                .get(owner)
+                .is_some_and(|address_state| address_state.operators.contains(address))
+        }
+
+        fn transfer(
+            &mut self,
+            token_id: &ContractTokenId,
+            amount: ContractTokenAmount,
+            from: &Address,
+            to: &Address,
+            state_builder: &mut StateBuilder<S>,
+        ) -> ContractResult<()> {
+            ensure!(self.contains_token(token_id), ContractError::InvalidTokenId);
+            // A transfer of zero tokens is valid and does not touch any balance.
+            if amount == 0.into() {
+                return Ok(());
+            }
+
+            {
+                let mut from_state = self
+                    .state
+                    .get_mut(from)
+                    .ok_or(ContractError::InsufficientFunds)?;
+                let mut from_balance = from_state
+                    .balances
+                    .entry(*token_id)
+                    .occupied_or(ContractError::InsufficientFunds)?;
+                ensure!(*from_balance >= amount, ContractError::InsufficientFunds);
+                *from_balance -= amount;
+            }
+
+            let mut to_state = self
+                .state
+                .entry(*to)
+                .or_insert_with(|| AddressState::empty(state_builder));
+            let mut to_balance = to_state.balances.entry(*token_id).or_insert(0.into());
+            *to_balance += amount;
+
+            Ok(())
+        }
+
+        fn add_operator(
+            &mut self,
+            owner: &Address,
+            operator: &Address,
+            state_builder: &mut StateBuilder<S>,
+        ) {
+            let mut owner_state = self
+                .state
+                .entry(*owner)
+                .or_insert_with(|| AddressState::empty(state_builder));
+            owner_state.operators.insert(*operator);
+        }
+
+        fn remove_operator(&mut self, owner: &Address, operator: &Address) {
+            self.state.entry(*owner).and_modify(|address_state| {
+                address_state.operators.remove(operator);
+            });
+        }
+
+        fn have_implementors(&self, std_id: &StandardIdentifierOwned) -> SupportResult {
+            if let Some(addresses) = self.implementors.get(std_id) {
+                SupportResult::SupportBy(addresses.to_vec())
+            } else {
+                SupportResult::NoSupport
+            }
+        }
+
+        fn set_implementors(
+            &mut self,
+            std_id: StandardIdentifierOwned,
+            implementors: Vec<ContractAddress>,
+        ) {
+            let _ = self.implementors.insert(std_id, implementors);
+        }
+    }
+
+    // The account deploying the contract becomes its owner and the only one
+    // allowed to mint.
+    #[init(
+        contract = "cis2_multi",
+        event = "Cis2Event<ContractTokenId, ContractTokenAmount>"
+    )]
+    pub fn cis2_multi_contract<S: HasStateApi>(
+        ctx: &impl HasInitContext,
+        state_builder: &mut StateBuilder<S>,
+    ) -> InitResult<State<S>> {
+        Ok(State::empty(ctx.init_origin(), state_builder))
+    }
+
+    #[receive(
+        contract = "cis2_multi",
+        name = "mint",
+        parameter = "MintParams",
+        error = "ContractError",
+        enable_logger,
+        mutable
+    )]
+    pub fn contract_mint<S: HasStateApi>(
+        ctx: &impl HasReceiveContext,
+        host: &mut impl HasHost<State<S>, StateApiType = S>,
+        logger: &mut impl HasLogger,
+    ) -> ContractResult<()> {
+        ensure!(
+            ctx.sender().matches_account(&host.state().owner),
+            ContractError::Unauthorized
         );
+        let params: MintParams = ctx.parameter_cursor().get()?;
+
+        let (state, builder) = host.state_and_builder();
+        let is_new = state.mint(
+            &params.token_id,
+            &params.metadata_url,
+            params.amount,
+            &params.owner,
+            builder,
+        );
+
+        logger.log(&Cis2Event::Mint(MintEvent {
+            token_id: params.token_id,
+            amount: params.amount,
+            owner: params.owner,
+        }))?;
+        if is_new {
+            logger.log(&Cis2Event::TokenMetadata::<_, ContractTokenAmount>(
+                TokenMetadataEvent {
+                    token_id: params.token_id,
+                    metadata_url: params.metadata_url,
+                },
+            ))?;
+        }
+        Ok(())
     }
 
-    // Store the NFT details in the contract storage
-    let nft = NFT::new(owner, identity, conditions);
-    let storage = Storage::new().set(&caller, &nft);
+    type TransferParameter = TransferParams<ContractTokenId, ContractTokenAmount>;
 
-    (
-        storage,
-        Event::new().info("NFT minted successfully"),
-    )
-}
+    #[receive(
+        contract = "cis2_multi",
+        name = "transfer",
+        parameter = "TransferParameter",
+        error = "ContractError",
+        enable_logger,
+        mutable
+    )]
+    pub fn contract_transfer<S: HasStateApi>(
+        ctx: &impl HasReceiveContext,
+        host: &mut impl HasHost<State<S>, StateApiType = S>,
+        logger: &mut impl HasLogger,
+    ) -> ContractResult<()> {
+        let TransferParams(transfers): TransferParameter = ctx.parameter_cursor().get()?;
+        let sender = ctx.sender();
+
+        for Transfer {
+            token_id,
+            amount,
+            from,
+            to,
+            data,
+        } in transfers
+        {
+            let (state, builder) = host.state_and_builder();
+            ensure!(
+                from == sender || state.is_operator(&sender, &from),
+                ContractError::Unauthorized
+            );
+            let to_address = to.address();
+            state.transfer(&token_id, amount, &from, &to_address, builder)?;
+
+            logger.log(&Cis2Event::Transfer(TransferEvent {
+                token_id,
+                amount,
+                from,
+                to: to_address,
+            }))?;
+
+            // Contracts receiving tokens are notified through the hook they named.
+            if let Receiver::Contract(address, function) = to {
+                let parameter = OnReceivingCis2Params {
+                    token_id,
+                    amount,
+                    from,
+                    data,
+                };
+                host.invoke_contract(
+                    &address,
+                    &parameter,
+                    function.as_entrypoint_name(),
+                    Amount::zero(),
+                )?;
+            }
+        }
+        Ok(())
+    }
+
+    #[receive(
+        contract = "cis2_multi",
+        name = "updateOperator",
+        parameter = "UpdateOperatorParams",
+        error = "ContractError",
+        enable_logger,
+        mutable
+    )]
+    pub fn contract_update_operator<S: HasStateApi>(
+        ctx: &impl HasReceiveContext,
+        host: &mut impl HasHost<State<S>, StateApiType = S>,
+        logger: &mut impl HasLogger,
+    ) -> ContractResult<()> {
+        let UpdateOperatorParams(params) = ctx.parameter_cursor().get()?;
+        let sender = ctx.sender();
+
+        let (state, builder) = host.state_and_builder();
+        for param in params {
+            match param.update {
+                OperatorUpdate::Add => state.add_operator(&sender, &param.operator, builder),
+                OperatorUpdate::Remove => state.remove_operator(&sender, &param.operator),
+            }
 
-#[concordium_contract(storage)]
-pub fn buy_nft(
-    ctx: &impl HasActions,
-    owner: AccountAddress,
-) -> (Storage, Event) {
-    let caller = ctx.get_caller();
-
-    // Retrieve the NFT details from the contract storage
-    let storage = Storage::new();
-    let nft: Option<NFT> = storage.get(&owner);
-
-    match nft {
-        Some(nft) => {
-            // Perform the conditions check
-            let conditions = nft.get_conditions();
-            let can_buy = check_conditions(&caller, &conditions);
-
-            if can_buy {
-                // Transfer ownership of the NFT to the caller
-                let new_nft = NFT::new(caller.clone(), nft.get_identity(), conditions);
-                let new_storage = storage.set(&caller, &new_nft).remove(&owner);
-
-                (
-                    new_storage,
-                    Event::new().info("NFT purchased successfully"),
-                )
+            logger.log(
+                &Cis2Event::<ContractTokenId, ContractTokenAmount>::UpdateOperator(
+                    UpdateOperatorEvent {
+                        owner: sender,
+                        operator: param.operator,
+                        update: param.update,
+                    },
+                ),
+            )?;
+        }
+        Ok(())
+    }
+
+    pub type ContractBalanceOfQueryParams = BalanceOfQueryParams<ContractTokenId>;
+
+    pub type ContractBalanceOfQueryResponse = BalanceOfQueryResponse<ContractTokenAmount>;
+
+    #[receive(
+        contract = "cis2_multi",
+        name = "balanceOf",
+        parameter = "ContractBalanceOfQueryParams",
+        return_value = "ContractBalanceOfQueryResponse",
+        error = "ContractError"
+    )]
+    pub fn contract_balance_of<S: HasStateApi>(
+        ctx: &impl HasReceiveContext,
+        host: &impl HasHost<State<S>, StateApiType = S>,
+    ) -> ContractResult<ContractBalanceOfQueryResponse> {
+        let params: ContractBalanceOfQueryParams = ctx.parameter_cursor().get()?;
+        let mut response = Vec::with_capacity(params.queries.len());
+        for query in params.queries {
+            let amount = host.state().balance(&query.token_id, &query.address)?;
+            response.push(amount);
+        }
+        Ok(BalanceOfQueryResponse::from(response))
+    }
+
+    #[receive(
+        contract = "cis2_multi",
+        name = "operatorOf",
+        parameter = "OperatorOfQueryParams",
+        return_value = "OperatorOfQueryResponse",
+        error = "ContractError"
+    )]
+    pub fn contract_operator_of<S: HasStateApi>(
+        ctx: &impl HasReceiveContext,
+        host: &impl HasHost<State<S>, StateApiType = S>,
+    ) -> ContractResult<OperatorOfQueryResponse> {
+        let params: OperatorOfQueryParams = ctx.parameter_cursor().get()?;
+        let mut response = Vec::with_capacity(params.queries.len());
+        for query in params.queries {
+            let is_operator = host.state().is_operator(&query.address, &query.owner);
+            response.push(is_operator);
+        }
+        Ok(OperatorOfQueryResponse::from(response))
+    }
+
+    pub type ContractTokenMetadataQueryParams = TokenMetadataQueryParams<ContractTokenId>;
+
+    #[receive(
+        contract = "cis2_multi",
+        name = "tokenMetadata",
+        parameter = "ContractTokenMetadataQueryParams",
+        return_value = "TokenMetadataQueryResponse",
+        error = "ContractError"
+    )]
+    pub fn contract_token_metadata<S: HasStateApi>(
+        ctx: &impl HasReceiveContext,
+        host: &impl HasHost<State<S>, StateApiType = S>,
+    ) -> ContractResult<TokenMetadataQueryResponse> {
+        let params: ContractTokenMetadataQueryParams = ctx.parameter_cursor().get()?;
+        let mut response = Vec::with_capacity(params.queries.len());
+        for token_id in params.queries {
+            let metadata_url = host
+                .state()
+                .tokens
+                .get(&token_id)
+                .map(|url| url.clone())
+                .ok_or(ContractError::InvalidTokenId)?;
+            response.push(metadata_url);
+        }
+        Ok(TokenMetadataQueryResponse::from(response))
+    }
+
+    #[receive(
+        contract = "cis2_multi",
+        name = "supports",
+        parameter = "SupportsQueryParams",
+        return_value = "SupportsQueryResponse",
+        error = "ContractError"
+    )]
+    pub fn contract_supports<S: HasStateApi>(
+        ctx: &impl HasReceiveContext,
+        host: &impl HasHost<State<S>, StateApiType = S>,
+    ) -> ContractResult<SupportsQueryResponse> {
+        let params: SupportsQueryParams = ctx.parameter_cursor().get()?;
+        let mut response = Vec::with_capacity(params.queries.len());
+        for std_id in params.queries {
+            if SUPPORTS_STANDARDS.contains(&std_id.as_standard_identifier()) {
+                response.push(SupportResult::Support);
             } else {
-                (
-                    storage,
-                    Event::new().error("Conditions not met to purchase the NFT"),
-                )
+                response.push(host.state().have_implementors(&std_id));
             }
         }
-        None => (
-            storage,
-            Event::new().error("NFT with the specified owner not found"),
-        ),
+        Ok(SupportsQueryResponse::from(response))
     }
-}
 
-fn check_conditions(caller: &AccountAddress, conditions: &str) -> bool {
-    // Perform your custom conditions check here
-    // Example: Allow the caller to buy if they are whitelisted
-    let whitelist = ["0x12345678", "0xabcdef01"];
-    whitelist.contains(&caller.to_hex())
+    // Lets the owner point `supports` queries for other standards at the
+    // contracts implementing them.
+    #[receive(
+        contract = "cis2_multi",
+        name = "setImplementors",
+        parameter = "SetImplementorsParams",
+        error = "ContractError",
+        mutable
+    )]
+    pub fn contract_set_implementor<S: HasStateApi>(
+        ctx: &impl HasReceiveContext,
+        host: &mut impl HasHost<State<S>, StateApiType = S>,
+    ) -> ContractResult<()> {
+        ensure!(
+            ctx.sender().matches_account(&host.state().owner),
+            ContractError::Unauthorized
+        );
+        let params: SetImplementorsParams = ctx.parameter_cursor().get()?;
+        host.state_mut()
+            .set_implementors(params.id, params.implementors);
+        Ok(())
+    }
 }
 
-struct NFT {
-    owner: AccountAddress,
-    identity: String,
-    conditions: String,
-}
+pub mod cis2_market {
+    use concordium_cis2::*;
+    use concordium_std::*;
 
-impl NFT {
-    fn new(owner: AccountAddress, identity: String, conditions: String) -> Self {
-        Self {
-            owner,
-            identity,
-            conditions,
+    // Listed tokens can come from any CIS-2 contract, so the id is kept in its
+    // raw byte form.
+    pub type ContractTokenId = TokenIdVec;
+
+    pub type ContractTokenAmount = TokenAmountU64;
+
+    pub type MarketResult<A> = Result<A, MarketError>;
+
+    // A listing is identified by the token and the account selling it.
+    #[derive(Serialize, SchemaType, Clone, Debug, PartialEq, Eq)]
+    pub struct ListingKey {
+        pub contract: ContractAddress,
+        pub token_id: ContractTokenId,
+        pub owner: AccountAddress,
+    }
+
+    #[derive(Serialize, SchemaType, Clone, Debug, PartialEq, Eq)]
+    pub struct Listing {
+        // Price in CCD of a single unit of the token.
+        pub price: Amount,
+        pub quantity: ContractTokenAmount,
+    }
+
+    #[derive(Serial, DeserialWithState)]
+    #[concordium(state_parameter = "S")]
+    pub struct State<S> {
+        listings: StateMap<ListingKey, Listing, S>,
+    }
+
+    #[derive(Serialize, SchemaType)]
+    pub struct ListParams {
+        pub contract: ContractAddress,
+        pub token_id: ContractTokenId,
+        pub price: Amount,
+        pub quantity: ContractTokenAmount,
+    }
+
+    #[derive(Serialize, SchemaType)]
+    pub struct DelistParams {
+        pub contract: ContractAddress,
+        pub token_id: ContractTokenId,
+    }
+
+    #[derive(Serialize, SchemaType)]
+    pub struct UpdatePriceParams {
+        pub contract: ContractAddress,
+        pub token_id: ContractTokenId,
+        pub price: Amount,
+    }
+
+    #[derive(Serialize, SchemaType)]
+    pub struct BuyParams {
+        pub contract: ContractAddress,
+        pub token_id: ContractTokenId,
+        pub owner: AccountAddress,
+        pub quantity: ContractTokenAmount,
+    }
+
+    // The most listings `view` returns at once. A larger `take` is clamped to
+    // it, which also keeps the response within its two byte length prefix.
+    pub const MAX_VIEW_TAKE: u32 = 100;
+
+    #[derive(Serialize, SchemaType)]
+    pub struct ViewParams {
+        pub skip: u32,
+        pub take: u32,
+    }
+
+    #[derive(Serialize, SchemaType, Debug, PartialEq, Eq)]
+    pub struct ListingView {
+        pub key: ListingKey,
+        pub listing: Listing,
+    }
+
+    #[derive(Serialize, SchemaType, Debug, PartialEq, Eq)]
+    pub struct ListingsView {
+        #[concordium(size_length = 2)]
+        pub listings: Vec<ListingView>,
+    }
+
+    #[derive(Serial, SchemaType, Debug, PartialEq, Eq)]
+    pub enum MarketEvent {
+        Listed {
+            key: ListingKey,
+            listing: Listing,
+        },
+        Delisted {
+            key: ListingKey,
+        },
+        PriceUpdated {
+            key: ListingKey,
+            price: Amount,
+        },
+        Sold {
+            key: ListingKey,
+            buyer: AccountAddress,
+            quantity: ContractTokenAmount,
+            paid: Amount,
+        },
+    }
+
+    #[derive(Serialize, Debug, PartialEq, Eq, Reject, SchemaType)]
+    pub enum MarketError {
+        #[from(ParseError)]
+        ParseParams,
+        // Only accounts can list and buy, since sellers are paid in CCD.
+        CalledByAContract,
+        // The market is not an operator of the seller's tokens.
+        NotOperator,
+        InsufficientBalance,
+        InvalidQuantity,
+        ListingNotFound,
+        // The CCD sent does not match the price of the requested quantity.
+        InvalidAmountPaid,
+        InvokeContractError,
+        InvokeTransferError,
+        LogFull,
+        LogMalformed,
+    }
+
+    impl From<LogError> for MarketError {
+        fn from(le: LogError) -> Self {
+            match le {
+                LogError::Full => Self::LogFull,
+                LogError::Malformed => Self::LogMalformed,
+            }
         }
     }
 
-    fn get_identity(&self) -> &str {
-        &self.identity
+    impl<T> From<Cis2ClientError<T>> for MarketError {
+        fn from(_e: Cis2ClientError<T>) -> Self {
+            Self::InvokeContractError
+        }
     }
 
-    fn get_conditions(&self) -> &str {
-        &self.conditions
+    impl From<TransferError> for MarketError {
+        fn from(_te: TransferError) -> Self {
+            Self::InvokeTransferError
+        }
     }
-}
 
-// Entry point for the CIS2 Multi contract
-#[no_mangle]
-pub extern "C" fn cis2_multi_contract() {
-    concordium_std::execute(cis2_multi::cis2_multi_contract);
-}
+    fn sender_account(ctx: &impl HasReceiveContext) -> MarketResult<AccountAddress> {
+        match ctx.sender() {
+            Address::Account(account) => Ok(account),
+            Address::Contract(_) => bail!(MarketError::CalledByAContract),
+        }
+    }
 
-// Entry point for the CIS2 Market contract
-#[no_mangle]
-pub extern "C" fn cis2_market_contract() {
-    concordium_std::execute(cis2_market::cis2_market_contract);
-}
+    #[init(contract = "cis2_market", event = "MarketEvent")]
+    pub fn cis2_market_contract<S: HasStateApi>(
+        _ctx: &impl HasInitContext,
+        state_builder: &mut StateBuilder<S>,
+    ) -> InitResult<State<S>> {
+        Ok(State {
+            listings: state_builder.new_map(),
+        })
+    }
 
+    // Lists `quantity` units of a token at `price` CCD each. The seller must
+    // have made this contract an operator on the token contract beforehand, so
+    // the tokens can be moved when they are bought.
+    #[receive(
+        contract = "cis2_market",
+        name = "list",
+        parameter = "ListParams",
+        error = "MarketError",
+        enable_logger,
+        mutable
+    )]
+    pub fn contract_list<S: HasStateApi>(
+        ctx: &impl HasReceiveContext,
+        host: &mut impl HasHost<State<S>, StateApiType = S>,
+        logger: &mut impl HasLogger,
+    ) -> MarketResult<()> {
+        let owner = sender_account(ctx)?;
+        let params: ListParams = ctx.parameter_cursor().get()?;
+        ensure!(params.quantity > 0.into(), MarketError::InvalidQuantity);
 
-In this code, I've created separate modules for the CIS2 Multi and CIS2 Market contracts. 
-The main code file contains the implementation of the mint_nft and buy_nft functions, the check_conditions function and the NFT struct.
-At the end of the code, I've added the entry points for the CIS2 Multi and CIS2 Market contracts using the no_mangle attribute. 
-These entry points will be used by the Concordium compiler to generate the appropriate contract files.
-Make sure to replace the unimplemented!() placeholder implementations in the cis2_multi_contract and cis2_market_contract functions with 
-the actual logic for the respective CIS2 contracts.
-Once you've added the CIS2 contracts and the main code file, you can run the following command to build the contracts:
+        let client = Cis2Client::new(params.contract);
+        let is_operator = client.operator_of::<_, ()>(
+            host,
+            Address::Account(owner),
+            Address::Contract(ctx.self_address()),
+        )?;
+        ensure!(is_operator, MarketError::NotOperator);
+        let balance: ContractTokenAmount = client.balance_of::<_, _, _, ()>(
+            host,
+            params.token_id.clone(),
+            Address::Account(owner),
+        )?;
+        ensure!(balance >= params.quantity, MarketError::InsufficientBalance);
+
+        let key = ListingKey {
+            contract: params.contract,
+            token_id: params.token_id,
+            owner,
+        };
+        let listing = Listing {
+            price: params.price,
+            quantity: params.quantity,
+        };
+        let _ = host
+            .state_mut()
+            .listings
+            .insert(key.clone(), listing.clone());
+
+        logger.log(&MarketEvent::Listed { key, listing })?;
+        Ok(())
+    }
+
+    #[receive(
+        contract = "cis2_market",
+        name = "delist",
+        parameter = "DelistParams",
+        error = "MarketError",
+        enable_logger,
+        mutable
+    )]
+    pub fn contract_delist<S: HasStateApi>(
+        ctx: &impl HasReceiveContext,
+        host: &mut impl HasHost<State<S>, StateApiType = S>,
+        logger: &mut impl HasLogger,
+    ) -> MarketResult<()> {
+        let owner = sender_account(ctx)?;
+        let params: DelistParams = ctx.parameter_cursor().get()?;
+        let key = ListingKey {
+            contract: params.contract,
+            token_id: params.token_id,
+            owner,
+        };
+        ensure!(
+            host.state().listings.get(&key).is_some(),
+            MarketError::ListingNotFound
+        );
+        host.state_mut().listings.remove(&key);
 
-cargo concordium build --out module.wasm --schema-out schema.bin
+        logger.log(&MarketEvent::Delisted { key })?;
+        Ok(())
+    }
 
-This command will compile the code and generate the module.wasm and schema.bin files, which can be deployed to the Concordium blockchain.
+    #[receive(
+        contract = "cis2_market",
+        name = "updatePrice",
+        parameter = "UpdatePriceParams",
+        error = "MarketError",
+        enable_logger,
+        mutable
+    )]
+    pub fn contract_update_price<S: HasStateApi>(
+        ctx: &impl HasReceiveContext,
+        host: &mut impl HasHost<State<S>, StateApiType = S>,
+        logger: &mut impl HasLogger,
+    ) -> MarketResult<()> {
+        let owner = sender_account(ctx)?;
+        let params: UpdatePriceParams = ctx.parameter_cursor().get()?;
+        let key = ListingKey {
+            contract: params.contract,
+            token_id: params.token_id,
+            owner,
+        };
+        host.state_mut()
+            .listings
+            .get_mut(&key)
+            .ok_or(MarketError::ListingNotFound)?
+            .price = params.price;
 
+        logger.log(&MarketEvent::PriceUpdated {
+            key,
+            price: params.price,
+        })?;
+        Ok(())
+    }
 
+    // Buys `quantity` units of a listing. The CCD attached must be exactly the
+    // listed price times the quantity; it is forwarded to the seller while the
+    // tokens are transferred to the buyer in the same transaction.
+    #[receive(
+        contract = "cis2_market",
+        name = "buy",
+        parameter = "BuyParams",
+        error = "MarketError",
+        enable_logger,
+        mutable,
+        payable
+    )]
+    pub fn contract_buy<S: HasStateApi>(
+        ctx: &impl HasReceiveContext,
+        host: &mut impl HasHost<State<S>, StateApiType = S>,
+        amount: Amount,
+        logger: &mut impl HasLogger,
+    ) -> MarketResult<()> {
+        let buyer = sender_account(ctx)?;
+        let params: BuyParams = ctx.parameter_cursor().get()?;
+        let key = ListingKey {
+            contract: params.contract,
+            token_id: params.token_id,
+            owner: params.owner,
+        };
+        let listing = host
+            .state()
+            .listings
+            .get(&key)
+            .map(|listing| listing.clone())
+            .ok_or(MarketError::ListingNotFound)?;
+        ensure!(
+            params.quantity > 0.into() && params.quantity <= listing.quantity,
+            MarketError::InvalidQuantity
+        );
+        let price = listing
+            .price
+            .micro_ccd
+            .checked_mul(params.quantity.0)
+            .map(Amount::from_micro_ccd)
+            .ok_or(MarketError::InvalidAmountPaid)?;
+        ensure!(amount == price, MarketError::InvalidAmountPaid);
 
+        // The listing is updated before calling out, so the same units cannot
+        // be sold twice by a re-entrant call.
+        let remaining = listing.quantity - params.quantity;
+        if remaining == 0.into() {
+            host.state_mut().listings.remove(&key);
+        } else if let Some(mut listing) = host.state_mut().listings.get_mut(&key) {
+            listing.quantity = remaining;
+        }
 
+        Cis2Client::new(key.contract).transfer::<_, _, _, ()>(
+            host,
+            Transfer {
+                token_id: key.token_id.clone(),
+                amount: params.quantity,
+                from: Address::Account(key.owner),
+                to: Receiver::from_account(buyer),
+                data: AdditionalData::empty(),
+            },
+        )?;
+        host.invoke_transfer(&key.owner, amount)?;
 
+        logger.log(&MarketEvent::Sold {
+            key,
+            buyer,
+            quantity: params.quantity,
+            paid: amount,
+        })?;
+        Ok(())
+    }
 
+    // Returns up to `take` listings, but no more than `MAX_VIEW_TAKE`, starting
+    // after the first `skip`.
+    #[receive(
+        contract = "cis2_market",
+        name = "view",
+        parameter = "ViewParams",
+        return_value = "ListingsView",
+        error = "MarketError"
+    )]
+    pub fn contract_view<S: HasStateApi>(
+        ctx: &impl HasReceiveContext,
+        host: &impl HasHost<State<S>, StateApiType = S>,
+    ) -> MarketResult<ListingsView> {
+        let params: ViewParams = ctx.parameter_cursor().get()?;
+        let listings = host
+            .state()
+            .listings
+            .iter()
+            .skip(params.skip as usize)
+            .take(params.take.min(MAX_VIEW_TAKE) as usize)
+            .map(|(key, listing)| ListingView {
+                key: key.clone(),
+                listing: listing.clone(),
+            })
+            .collect();
+        Ok(ListingsView { listings })
+    }
+}
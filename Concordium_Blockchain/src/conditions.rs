@@ -0,0 +1,218 @@
+use concordium_std::*;
+
+// Purchase conditions attached to each NFT.
+//
+// A condition string is a list of predicates combined with `AND`/`OR`, where
+// `AND` binds tighter than `OR` and parentheses can be used for grouping:
+//
+//   allow(<account hex>, ...)      the buyer is one of the listed accounts
+//   min_balance(<micro CCD>)       the buyer holds at least this much CCD
+//   window(<start ms>, <end ms>)   the purchase happens within this time window
+//
+// For example `allow(0x01..) OR (window(0, 86400000) AND min_balance(1000000))`.
+// An empty string puts no restriction on the buyer beyond the verifier
+// attestation every purchase needs.
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum Condition {
+    Allowlist(Vec<AccountAddress>),
+    MinBalance(Amount),
+    TimeWindow { start: Timestamp, end: Timestamp },
+    And(Vec<Condition>),
+    Or(Vec<Condition>),
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct ConditionParseError;
+
+// What is known about a purchase when its conditions are evaluated.
+pub struct ConditionContext {
+    pub buyer: AccountAddress,
+    // Only queried when the condition checks a balance.
+    pub balance: Option<Amount>,
+    pub slot_time: Timestamp,
+}
+
+impl Condition {
+    pub fn parse(input: &str) -> Result<Self, ConditionParseError> {
+        let mut parser = Parser { input, pos: 0 };
+        if parser.at_end() {
+            return Ok(Condition::And(Vec::new()));
+        }
+        let condition = parser.expr()?;
+        ensure!(parser.at_end(), ConditionParseError);
+        Ok(condition)
+    }
+
+    pub fn needs_balance(&self) -> bool {
+        match self {
+            Condition::MinBalance(_) => true,
+            Condition::And(conditions) | Condition::Or(conditions) => {
+                conditions.iter().any(Condition::needs_balance)
+            }
+            _ => false,
+        }
+    }
+
+    pub fn evaluate(&self, ctx: &ConditionContext) -> bool {
+        match self {
+            Condition::Allowlist(accounts) => accounts.contains(&ctx.buyer),
+            Condition::MinBalance(min) => ctx.balance.is_some_and(|balance| balance >= *min),
+            Condition::TimeWindow { start, end } => {
+                *start <= ctx.slot_time && ctx.slot_time <= *end
+            }
+            Condition::And(conditions) => conditions.iter().all(|c| c.evaluate(ctx)),
+            Condition::Or(conditions) => conditions.iter().any(|c| c.evaluate(ctx)),
+        }
+    }
+}
+
+struct Parser<'a> {
+    input: &'a str,
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn rest(&self) -> &'a str {
+        &self.input[self.pos..]
+    }
+
+    fn skip_whitespace(&mut self) {
+        let rest = self.rest();
+        self.pos += rest.len() - rest.trim_start().len();
+    }
+
+    fn at_end(&mut self) -> bool {
+        self.skip_whitespace();
+        self.rest().is_empty()
+    }
+
+    fn eat(&mut self, symbol: &str) -> bool {
+        self.skip_whitespace();
+        if self.rest().starts_with(symbol) {
+            self.pos += symbol.len();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, symbol: &str) -> Result<(), ConditionParseError> {
+        ensure!(self.eat(symbol), ConditionParseError);
+        Ok(())
+    }
+
+    // Reads an identifier or literal made of ASCII letters, digits and `_`.
+    fn word(&mut self) -> &'a str {
+        self.skip_whitespace();
+        let rest = self.rest();
+        let len = rest
+            .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+            .unwrap_or(rest.len());
+        self.pos += len;
+        &rest[..len]
+    }
+
+    fn eat_keyword(&mut self, keyword: &str) -> bool {
+        let start = self.pos;
+        if self.word().eq_ignore_ascii_case(keyword) {
+            true
+        } else {
+            self.pos = start;
+            false
+        }
+    }
+
+    fn expr(&mut self) -> Result<Condition, ConditionParseError> {
+        let mut terms = vec![self.term()?];
+        while self.eat_keyword("OR") {
+            terms.push(self.term()?);
+        }
+        Ok(if terms.len() == 1 {
+            terms.remove(0)
+        } else {
+            Condition::Or(terms)
+        })
+    }
+
+    fn term(&mut self) -> Result<Condition, ConditionParseError> {
+        let mut factors = vec![self.factor()?];
+        while self.eat_keyword("AND") {
+            factors.push(self.factor()?);
+        }
+        Ok(if factors.len() == 1 {
+            factors.remove(0)
+        } else {
+            Condition::And(factors)
+        })
+    }
+
+    fn factor(&mut self) -> Result<Condition, ConditionParseError> {
+        if self.eat("(") {
+            let condition = self.expr()?;
+            self.expect(")")?;
+            return Ok(condition);
+        }
+        match self.word() {
+            "allow" => {
+                let args = self.args()?;
+                ensure!(!args.is_empty(), ConditionParseError);
+                let accounts = args
+                    .into_iter()
+                    .map(parse_account)
+                    .collect::<Result<_, _>>()?;
+                Ok(Condition::Allowlist(accounts))
+            }
+            "min_balance" => match self.args()?[..] {
+                [amount] => Ok(Condition::MinBalance(Amount::from_micro_ccd(parse_u64(
+                    amount,
+                )?))),
+                _ => Err(ConditionParseError),
+            },
+            "window" => match self.args()?[..] {
+                [start, end] => {
+                    let start = Timestamp::from_timestamp_millis(parse_u64(start)?);
+                    let end = Timestamp::from_timestamp_millis(parse_u64(end)?);
+                    ensure!(start <= end, ConditionParseError);
+                    Ok(Condition::TimeWindow { start, end })
+                }
+                _ => Err(ConditionParseError),
+            },
+            _ => Err(ConditionParseError),
+        }
+    }
+
+    // Parses a parenthesised, comma separated argument list.
+    fn args(&mut self) -> Result<Vec<&'a str>, ConditionParseError> {
+        self.expect("(")?;
+        let mut args = Vec::new();
+        if self.eat(")") {
+            return Ok(args);
+        }
+        loop {
+            let arg = self.word();
+            ensure!(!arg.is_empty(), ConditionParseError);
+            args.push(arg);
+            if self.eat(")") {
+                return Ok(args);
+            }
+            self.expect(",")?;
+        }
+    }
+}
+
+fn parse_u64(literal: &str) -> Result<u64, ConditionParseError> {
+    literal.parse().map_err(|_| ConditionParseError)
+}
+
+// Accounts are written as the hex encoding of their 32 bytes, optionally
+// prefixed with `0x`.
+fn parse_account(literal: &str) -> Result<AccountAddress, ConditionParseError> {
+    let hex = literal.strip_prefix("0x").unwrap_or(literal);
+    ensure!(hex.len() == 64 && hex.is_ascii(), ConditionParseError);
+    let mut bytes = [0u8; 32];
+    for (i, byte) in bytes.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(&hex[2 * i..2 * i + 2], 16).map_err(|_| ConditionParseError)?;
+    }
+    Ok(AccountAddress(bytes))
+}
@@ -1,79 +1,730 @@
+use concordium_cis2::TokenIdU32;
 use concordium_std::*;
 
-#[concordium_contract(storage)]
-pub fn mint_nft(
-    ctx: &impl HasActions,
-    owner: AccountAddress,
-    identity: String,
-    conditions: String,
-) -> (Storage, Event) {
-    let caller = ctx.get_caller();
+pub mod attributes;
+mod cis2_contract;
+mod conditions;
+mod statements;
+#[cfg(test)]
+mod verifier;
+
+use crate::attributes::{AttributeTag, Attributes};
+use crate::conditions::{Condition, ConditionContext, ConditionParseError};
+use crate::statements::StatementError;
+
+pub type TokenId = TokenIdU32;
+// Id under which the id-verifier backend publishes a signing key at `/api/keys`.
+pub type KeyId = String;
+
+#[derive(Serial, DeserialWithState)]
+#[concordium(state_parameter = "S")]
+pub struct State<S> {
+    // Account that manages the allowlist and the attribute sets.
+    admin: AccountAddress,
+    // Keys of the id-verifier backend whose signatures admit buyers, by id.
+    verifier_keys: StateMap<KeyId, VerifierKey, S>,
+    // Hash of the policy buyers must have proven to the id-verifier.
+    policy_hash: HashSha2256,
+    // Accounts that are allowed to buy NFTs.
+    allowlist: StateSet<AccountAddress, S>,
+    next_token_id: u32,
+    nfts: StateMap<TokenId, NFT, S>,
+    // Index of the tokens held by each account, kept in sync with `nfts`.
+    owners: StateMap<AccountAddress, StateSet<TokenId, S>, S>,
+    // Named sets of attribute values that membership statements refer to.
+    attribute_sets: StateMap<String, AttributeSet, S>,
+}
+
+#[derive(Serialize, SchemaType, Clone)]
+pub struct AttributeSet {
+    pub attribute_tag: AttributeTag,
+    #[concordium(size_length = 2)]
+    pub values: Vec<String>,
+}
+
+#[derive(Serialize, SchemaType, Clone)]
+pub struct VerifierKey {
+    pub key: PublicKeyEd25519,
+    pub valid_from: Timestamp,
+    // The key is retired from this time on.
+    pub valid_until: Option<Timestamp>,
+}
+
+impl VerifierKey {
+    fn is_active(&self, now: Timestamp) -> bool {
+        self.valid_from <= now && self.valid_until.is_none_or(|until| now < until)
+    }
+}
+
+impl<S: HasStateApi> State<S> {
+    fn add_to_owner(
+        &mut self,
+        owner: AccountAddress,
+        token_id: TokenId,
+        state_builder: &mut StateBuilder<S>,
+    ) {
+        self.owners
+            .entry(owner)
+            .or_insert_with(|| state_builder.new_set())
+            .insert(token_id);
+    }
+
+    fn remove_from_owner(&mut self, owner: &AccountAddress, token_id: &TokenId) {
+        self.owners.entry(*owner).and_modify(|tokens| {
+            tokens.remove(token_id);
+        });
+    }
+}
+
+#[derive(Serialize, SchemaType)]
+pub struct InitParams {
+    pub verifier_key: VerifierKeyParams,
+    // SHA-256 of the name of the id-verifier policy, e.g. `age18`, as the backend
+    // puts it in attestations. Unlike the statement of an age policy it does not
+    // change from day to day.
+    pub policy_hash: HashSha2256,
+}
+
+#[derive(Serialize, SchemaType)]
+pub struct VerifierKeyParams {
+    pub key_id: KeyId,
+    pub key: VerifierKey,
+}
+
+#[derive(Serialize, SchemaType)]
+pub struct AllowlistParams {
+    #[concordium(size_length = 2)]
+    pub accounts: Vec<AccountAddress>,
+}
+
+#[derive(Serialize, SchemaType)]
+pub struct RangeParams {
+    pub attribute_tag: AttributeTag,
+    // Inclusive lower and exclusive upper bound, as `YYYYMMDD` dates.
+    pub lower: String,
+    pub upper: String,
+}
+
+#[derive(Serialize, SchemaType)]
+pub struct AttributeSetParams {
+    pub name: String,
+    pub set: AttributeSet,
+}
+
+#[derive(Serialize, SchemaType)]
+pub struct MembershipParams {
+    pub set_name: String,
+    // Whether the attribute must be in the set, or must not be.
+    pub member: bool,
+}
+
+#[derive(Serialize, SchemaType)]
+pub struct MintParams {
+    pub owner: AccountAddress,
+    pub identity: String,
+    pub conditions: String,
+}
+
+// Issued by the id-verifier backend once an identity proof was accepted. The
+// backend signs exactly the serialization of this structure.
+#[derive(Serialize, SchemaType, Clone)]
+pub struct Attestation {
+    pub account: AccountAddress,
+    pub policy_hash: HashSha2256,
+    pub challenge: [u8; 32],
+    pub issued_at: Timestamp,
+    pub expires_at: Timestamp,
+    // When set, the attestation is only valid for this contract.
+    pub contract: Option<ContractAddress>,
+    // The verifier key that signed the attestation.
+    pub key_id: KeyId,
+}
+
+#[derive(Serialize, SchemaType)]
+pub struct BuyParams {
+    pub token_id: TokenId,
+    // Attestation of the id-verifier backend that the buyer proved their identity.
+    pub attestation: Attestation,
+    // Signature of the id-verifier backend over the attestation.
+    pub signature: SignatureEd25519,
+}
+
+#[derive(Serial, SchemaType)]
+pub enum NftEvent {
+    Minted {
+        token_id: TokenId,
+        owner: AccountAddress,
+    },
+    Purchased {
+        token_id: TokenId,
+        from: AccountAddress,
+        to: AccountAddress,
+    },
+    AddedToAllowlist {
+        account: AccountAddress,
+    },
+    RemovedFromAllowlist {
+        account: AccountAddress,
+    },
+    VerifierKeyAdded {
+        key_id: KeyId,
+        key: PublicKeyEd25519,
+    },
+    VerifierKeyRetired {
+        key_id: KeyId,
+    },
+    AttributeSetUpdated {
+        name: String,
+    },
+    AttributeSetRemoved {
+        name: String,
+    },
+}
+
+#[derive(Serialize, Debug, PartialEq, Eq, Reject, SchemaType)]
+pub enum NftError {
+    #[from(ParseError)]
+    ParseParams,
+    // Only accounts can hold NFTs.
+    CalledByAContract,
+    // Only the admin can update the allowlist.
+    Unauthorized,
+    NotAllowlisted,
+    NotOwner,
+    TokenNotFound,
+    AlreadyOwner,
+    InvalidAttestation,
+    AttestationExpired,
+    AttestationNotYetValid,
+    UnknownVerifierKey,
+    // The key is not valid yet or has been retired.
+    VerifierKeyNotActive,
+    VerifierKeyExists,
+    InvalidConditions,
+    ConditionsNotMet,
+    // The requested statement would not ask for anything.
+    InvalidStatement,
+    AttributeNotRangeable,
+    InvalidRangeBounds,
+    AttributeNotSettable,
+    InvalidSetValues,
+    AttributeSetNotFound,
+    LogFull,
+    LogMalformed,
+}
+
+impl From<LogError> for NftError {
+    fn from(le: LogError) -> Self {
+        match le {
+            LogError::Full => Self::LogFull,
+            LogError::Malformed => Self::LogMalformed,
+        }
+    }
+}
+
+impl From<ConditionParseError> for NftError {
+    fn from(_e: ConditionParseError) -> Self {
+        Self::InvalidConditions
+    }
+}
+
+impl From<StatementError> for NftError {
+    fn from(e: StatementError) -> Self {
+        match e {
+            StatementError::NotRangeable => Self::AttributeNotRangeable,
+            StatementError::InvalidBounds => Self::InvalidRangeBounds,
+            StatementError::NotSettable => Self::AttributeNotSettable,
+            StatementError::InvalidSetValue => Self::InvalidSetValues,
+        }
+    }
+}
+
+fn sender_account(ctx: &impl HasReceiveContext) -> Result<AccountAddress, NftError> {
+    match ctx.sender() {
+        Address::Account(account) => Ok(account),
+        Address::Contract(_) => bail!(NftError::CalledByAContract),
+    }
+}
+
+#[init(
+    contract = "identity_nft",
+    parameter = "InitParams",
+    event = "NftEvent"
+)]
+pub fn contract_init<S: HasStateApi>(
+    ctx: &impl HasInitContext,
+    state_builder: &mut StateBuilder<S>,
+) -> InitResult<State<S>> {
+    let params: InitParams = ctx.parameter_cursor().get()?;
+    let mut verifier_keys = state_builder.new_map();
+    let _ = verifier_keys.insert(params.verifier_key.key_id, params.verifier_key.key);
+    Ok(State {
+        admin: ctx.init_origin(),
+        verifier_keys,
+        policy_hash: params.policy_hash,
+        allowlist: state_builder.new_set(),
+        next_token_id: 0,
+        nfts: state_builder.new_map(),
+        owners: state_builder.new_map(),
+        attribute_sets: state_builder.new_map(),
+    })
+}
+
+#[receive(
+    contract = "identity_nft",
+    name = "mintNft",
+    parameter = "MintParams",
+    return_value = "TokenId",
+    error = "NftError",
+    enable_logger,
+    mutable
+)]
+pub fn mint_nft<S: HasStateApi>(
+    ctx: &impl HasReceiveContext,
+    host: &mut impl HasHost<State<S>, StateApiType = S>,
+    logger: &mut impl HasLogger,
+) -> Result<TokenId, NftError> {
+    let caller = sender_account(ctx)?;
+    let params: MintParams = ctx.parameter_cursor().get()?;
 
     // Verify the ownership and identity
-    if caller != owner {
-        return (
-            Storage::new(),
-            Event::new().error("Caller is not the owner of the NFT"),
-        );
+    ensure!(caller == params.owner, NftError::NotOwner);
+    // Reject conditions that could never be checked at purchase time
+    Condition::parse(&params.conditions)?;
+
+    // Every NFT gets a fresh token id, so an account can hold any number of them
+    let (state, state_builder) = host.state_and_builder();
+    let token_id = TokenIdU32(state.next_token_id);
+    state.next_token_id += 1;
+    let nft = NFT::new(params.owner, params.identity, params.conditions);
+    let _ = state.nfts.insert(token_id, nft);
+    state.add_to_owner(params.owner, token_id, state_builder);
+
+    logger.log(&NftEvent::Minted {
+        token_id,
+        owner: params.owner,
+    })?;
+    Ok(token_id)
+}
+
+#[receive(
+    contract = "identity_nft",
+    name = "buyNft",
+    parameter = "BuyParams",
+    error = "NftError",
+    enable_logger,
+    crypto_primitives,
+    mutable
+)]
+pub fn buy_nft<S: HasStateApi>(
+    ctx: &impl HasReceiveContext,
+    host: &mut impl HasHost<State<S>, StateApiType = S>,
+    logger: &mut impl HasLogger,
+    crypto_primitives: &impl HasCryptoPrimitives,
+) -> Result<(), NftError> {
+    let caller = sender_account(ctx)?;
+    ensure!(
+        host.state().allowlist.contains(&caller),
+        NftError::NotAllowlisted
+    );
+    let BuyParams {
+        token_id,
+        attestation,
+        signature,
+    } = ctx.parameter_cursor().get()?;
+
+    // Only accounts the id-verifier has accepted an identity proof from can buy,
+    // whatever the conditions of the NFT are.
+    check_attestation(
+        ctx,
+        host,
+        crypto_primitives,
+        &caller,
+        &attestation,
+        signature,
+    )?;
+
+    let (seller, conditions) = {
+        let nft = host
+            .state()
+            .nfts
+            .get(&token_id)
+            .ok_or(NftError::TokenNotFound)?;
+        (nft.owner, nft.get_conditions().to_string())
+    };
+    ensure!(seller != caller, NftError::AlreadyOwner);
+
+    // Perform the conditions check
+    ensure!(
+        check_conditions(ctx, host, &caller, &conditions)?,
+        NftError::ConditionsNotMet
+    );
+
+    // Transfer ownership of the NFT to the caller
+    let (state, state_builder) = host.state_and_builder();
+    if let Some(mut nft) = state.nfts.get_mut(&token_id) {
+        nft.owner = caller;
     }
+    state.remove_from_owner(&seller, &token_id);
+    state.add_to_owner(caller, token_id, state_builder);
 
-    // Store the NFT details in the contract storage
-    let nft = NFT::new(owner, identity, conditions);
-    let storage = Storage::new().set(&caller, &nft);
+    logger.log(&NftEvent::Purchased {
+        token_id,
+        from: seller,
+        to: caller,
+    })?;
+    Ok(())
+}
 
-    (
-        storage,
-        Event::new().info("NFT minted successfully"),
-    )
+#[receive(
+    contract = "identity_nft",
+    name = "addToAllowlist",
+    parameter = "AllowlistParams",
+    error = "NftError",
+    enable_logger,
+    mutable
+)]
+pub fn add_to_allowlist<S: HasStateApi>(
+    ctx: &impl HasReceiveContext,
+    host: &mut impl HasHost<State<S>, StateApiType = S>,
+    logger: &mut impl HasLogger,
+) -> Result<(), NftError> {
+    ensure!(
+        ctx.sender().matches_account(&host.state().admin),
+        NftError::Unauthorized
+    );
+    let params: AllowlistParams = ctx.parameter_cursor().get()?;
+    for account in params.accounts {
+        // Accounts that are already on the list are skipped without an event
+        if host.state_mut().allowlist.insert(account) {
+            logger.log(&NftEvent::AddedToAllowlist { account })?;
+        }
+    }
+    Ok(())
 }
 
-#[concordium_contract(storage)]
-pub fn buy_nft(
-    ctx: &impl HasActions,
-    owner: AccountAddress,
-) -> (Storage, Event) {
-    let caller = ctx.get_caller();
-
-    // Retrieve the NFT details from the contract storage
-    let storage = Storage::new();
-    let nft: Option<NFT> = storage.get(&owner);
-
-    match nft {
-        Some(nft) => {
-            // Perform the conditions check
-            let conditions = nft.get_conditions();
-            let can_buy = check_conditions(&caller, &conditions);
-
-            if can_buy {
-                // Transfer ownership of the NFT to the caller
-                let new_nft = NFT::new(caller.clone(), nft.get_identity(), conditions);
-                let new_storage = storage.set(&caller, &new_nft).remove(&owner);
-
-                (
-                    new_storage,
-                    Event::new().info("NFT purchased successfully"),
-                )
-            } else {
-                (
-                    storage,
-                    Event::new().error("Conditions not met to purchase the NFT"),
-                )
-            }
+#[receive(
+    contract = "identity_nft",
+    name = "removeFromAllowlist",
+    parameter = "AllowlistParams",
+    error = "NftError",
+    enable_logger,
+    mutable
+)]
+pub fn remove_from_allowlist<S: HasStateApi>(
+    ctx: &impl HasReceiveContext,
+    host: &mut impl HasHost<State<S>, StateApiType = S>,
+    logger: &mut impl HasLogger,
+) -> Result<(), NftError> {
+    ensure!(
+        ctx.sender().matches_account(&host.state().admin),
+        NftError::Unauthorized
+    );
+    let params: AllowlistParams = ctx.parameter_cursor().get()?;
+    for account in params.accounts {
+        if host.state_mut().allowlist.remove(&account) {
+            logger.log(&NftEvent::RemovedFromAllowlist { account })?;
         }
-        None => (
-            storage,
-            Event::new().error("NFT with the specified owner not found"),
-        ),
     }
+    Ok(())
 }
 
-fn check_conditions(caller: &AccountAddress, conditions: &str) -> bool {
-    // Perform your custom conditions check here
-    // Example: Allow the caller to buy if they are whitelisted
-    let whitelist = ["0x12345678", "0xabcdef01"];
-    whitelist.contains(&caller.to_hex())
+#[receive(
+    contract = "identity_nft",
+    name = "isAllowlisted",
+    parameter = "AccountAddress",
+    return_value = "bool",
+    error = "NftError"
+)]
+pub fn is_allowlisted<S: HasStateApi>(
+    ctx: &impl HasReceiveContext,
+    host: &impl HasHost<State<S>, StateApiType = S>,
+) -> Result<bool, NftError> {
+    let account: AccountAddress = ctx.parameter_cursor().get()?;
+    Ok(host.state().allowlist.contains(&account))
 }
 
+#[receive(
+    contract = "identity_nft",
+    name = "addVerifierKey",
+    parameter = "VerifierKeyParams",
+    error = "NftError",
+    enable_logger,
+    mutable
+)]
+pub fn add_verifier_key<S: HasStateApi>(
+    ctx: &impl HasReceiveContext,
+    host: &mut impl HasHost<State<S>, StateApiType = S>,
+    logger: &mut impl HasLogger,
+) -> Result<(), NftError> {
+    ensure!(
+        ctx.sender().matches_account(&host.state().admin),
+        NftError::Unauthorized
+    );
+    let params: VerifierKeyParams = ctx.parameter_cursor().get()?;
+    let key = params.key.key;
+    // A key id always refers to the same key, so a replaced key gets a new id
+    ensure!(
+        host.state_mut()
+            .verifier_keys
+            .insert(params.key_id.clone(), params.key)
+            .is_none(),
+        NftError::VerifierKeyExists
+    );
+    logger.log(&NftEvent::VerifierKeyAdded {
+        key_id: params.key_id,
+        key,
+    })?;
+    Ok(())
+}
+
+#[receive(
+    contract = "identity_nft",
+    name = "retireVerifierKey",
+    parameter = "KeyId",
+    error = "NftError",
+    enable_logger,
+    mutable
+)]
+pub fn retire_verifier_key<S: HasStateApi>(
+    ctx: &impl HasReceiveContext,
+    host: &mut impl HasHost<State<S>, StateApiType = S>,
+    logger: &mut impl HasLogger,
+) -> Result<(), NftError> {
+    ensure!(
+        ctx.sender().matches_account(&host.state().admin),
+        NftError::Unauthorized
+    );
+    let key_id: KeyId = ctx.parameter_cursor().get()?;
+    let now = ctx.metadata().slot_time();
+    {
+        let mut key = host
+            .state_mut()
+            .verifier_keys
+            .get_mut(&key_id)
+            .ok_or(NftError::UnknownVerifierKey)?;
+        // Signatures of a retired key are no longer accepted from now on
+        if key.valid_until.is_none_or(|until| now < until) {
+            key.valid_until = Some(now);
+        }
+    }
+    logger.log(&NftEvent::VerifierKeyRetired { key_id })?;
+    Ok(())
+}
+
+#[receive(
+    contract = "identity_nft",
+    name = "tokensOf",
+    parameter = "AccountAddress",
+    return_value = "Vec<TokenId>",
+    error = "NftError"
+)]
+pub fn tokens_of<S: HasStateApi>(
+    ctx: &impl HasReceiveContext,
+    host: &impl HasHost<State<S>, StateApiType = S>,
+) -> Result<Vec<TokenId>, NftError> {
+    let owner: AccountAddress = ctx.parameter_cursor().get()?;
+    let tokens = host
+        .state()
+        .owners
+        .get(&owner)
+        .map(|tokens| tokens.iter().map(|token_id| *token_id).collect())
+        .unwrap_or_default();
+    Ok(tokens)
+}
+
+// Returns the statement JSON asking the prover to reveal every attribute that is
+// set in the parameter.
+#[receive(
+    contract = "identity_nft",
+    name = "generateRevealStatement",
+    parameter = "Attributes",
+    return_value = "String",
+    error = "NftError"
+)]
+pub fn generate_reveal_statement<S: HasStateApi>(
+    ctx: &impl HasReceiveContext,
+    _host: &impl HasHost<State<S>, StateApiType = S>,
+) -> Result<String, NftError> {
+    let attributes: Attributes = ctx.parameter_cursor().get()?;
+    let statement = statements::reveal_statement(&attributes);
+    ensure!(!statement.is_empty(), NftError::InvalidStatement);
+    Ok(statements::to_json(&statement))
+}
+
+// Returns the statement JSON asking the prover to show that a date attribute,
+// such as the date of birth for an age check, lies in the given range.
+#[receive(
+    contract = "identity_nft",
+    name = "generateRangeStatement",
+    parameter = "RangeParams",
+    return_value = "String",
+    error = "NftError"
+)]
+pub fn generate_range_statement<S: HasStateApi>(
+    ctx: &impl HasReceiveContext,
+    _host: &impl HasHost<State<S>, StateApiType = S>,
+) -> Result<String, NftError> {
+    let params: RangeParams = ctx.parameter_cursor().get()?;
+    let statement =
+        statements::range_statement(params.attribute_tag, &params.lower, &params.upper)?;
+    Ok(statements::to_json(&[statement]))
+}
+
+// Creates or replaces a named attribute set, e.g. "eu" holding the country
+// codes of the EU member states.
+#[receive(
+    contract = "identity_nft",
+    name = "setAttributeSet",
+    parameter = "AttributeSetParams",
+    error = "NftError",
+    enable_logger,
+    mutable
+)]
+pub fn set_attribute_set<S: HasStateApi>(
+    ctx: &impl HasReceiveContext,
+    host: &mut impl HasHost<State<S>, StateApiType = S>,
+    logger: &mut impl HasLogger,
+) -> Result<(), NftError> {
+    ensure!(
+        ctx.sender().matches_account(&host.state().admin),
+        NftError::Unauthorized
+    );
+    let AttributeSetParams { name, set } = ctx.parameter_cursor().get()?;
+    // Only sets that can be turned into a statement are stored
+    statements::set_statement(set.attribute_tag, set.values.clone(), true)?;
+    let _ = host.state_mut().attribute_sets.insert(name.clone(), set);
+
+    logger.log(&NftEvent::AttributeSetUpdated { name })?;
+    Ok(())
+}
+
+#[receive(
+    contract = "identity_nft",
+    name = "removeAttributeSet",
+    parameter = "String",
+    error = "NftError",
+    enable_logger,
+    mutable
+)]
+pub fn remove_attribute_set<S: HasStateApi>(
+    ctx: &impl HasReceiveContext,
+    host: &mut impl HasHost<State<S>, StateApiType = S>,
+    logger: &mut impl HasLogger,
+) -> Result<(), NftError> {
+    ensure!(
+        ctx.sender().matches_account(&host.state().admin),
+        NftError::Unauthorized
+    );
+    let name: String = ctx.parameter_cursor().get()?;
+    ensure!(
+        host.state().attribute_sets.get(&name).is_some(),
+        NftError::AttributeSetNotFound
+    );
+    host.state_mut().attribute_sets.remove(&name);
+
+    logger.log(&NftEvent::AttributeSetRemoved { name })?;
+    Ok(())
+}
+
+// Returns the statement JSON asking the prover to show that an attribute is,
+// or is not, in one of the stored attribute sets.
+#[receive(
+    contract = "identity_nft",
+    name = "generateMembershipStatement",
+    parameter = "MembershipParams",
+    return_value = "String",
+    error = "NftError"
+)]
+pub fn generate_membership_statement<S: HasStateApi>(
+    ctx: &impl HasReceiveContext,
+    host: &impl HasHost<State<S>, StateApiType = S>,
+) -> Result<String, NftError> {
+    let params: MembershipParams = ctx.parameter_cursor().get()?;
+    let set = host
+        .state()
+        .attribute_sets
+        .get(&params.set_name)
+        .map(|set| set.clone())
+        .ok_or(NftError::AttributeSetNotFound)?;
+    let statement = statements::set_statement(set.attribute_tag, set.values, params.member)?;
+    Ok(statements::to_json(&[statement]))
+}
+
+fn check_attestation<S: HasStateApi>(
+    ctx: &impl HasReceiveContext,
+    host: &impl HasHost<State<S>, StateApiType = S>,
+    crypto_primitives: &impl HasCryptoPrimitives,
+    caller: &AccountAddress,
+    attestation: &Attestation,
+    signature: SignatureEd25519,
+) -> Result<(), NftError> {
+    ensure!(attestation.account == *caller, NftError::InvalidAttestation);
+    ensure!(
+        attestation
+            .contract
+            .is_none_or(|contract| contract == ctx.self_address()),
+        NftError::InvalidAttestation
+    );
+    ensure!(
+        attestation.policy_hash == host.state().policy_hash,
+        NftError::InvalidAttestation
+    );
+    let slot_time = ctx.metadata().slot_time();
+    let verifier_key = host
+        .state()
+        .verifier_keys
+        .get(&attestation.key_id)
+        .map(|key| key.clone())
+        .ok_or(NftError::UnknownVerifierKey)?;
+    ensure!(
+        verifier_key.is_active(slot_time),
+        NftError::VerifierKeyNotActive
+    );
+    ensure!(
+        attestation.issued_at <= slot_time,
+        NftError::AttestationNotYetValid
+    );
+    ensure!(
+        slot_time <= attestation.expires_at,
+        NftError::AttestationExpired
+    );
+    ensure!(
+        crypto_primitives.verify_ed25519_signature(
+            verifier_key.key,
+            signature,
+            &to_bytes(attestation)
+        ),
+        NftError::InvalidAttestation
+    );
+    Ok(())
+}
+
+fn check_conditions<S: HasStateApi>(
+    ctx: &impl HasReceiveContext,
+    host: &impl HasHost<State<S>, StateApiType = S>,
+    caller: &AccountAddress,
+    conditions: &str,
+) -> Result<bool, NftError> {
+    let condition = Condition::parse(conditions)?;
+    // The balance is only queried when a condition depends on it
+    let balance = if condition.needs_balance() {
+        host.account_balance(*caller)
+            .ok()
+            .map(|balance| balance.total)
+    } else {
+        None
+    };
+    let condition_ctx = ConditionContext {
+        buyer: *caller,
+        balance,
+        slot_time: ctx.metadata().slot_time(),
+    };
+    Ok(condition.evaluate(&condition_ctx))
+}
+
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Serialize, SchemaType, Clone)]
 struct NFT {
     owner: AccountAddress,
     identity: String,
@@ -89,10 +740,6 @@ impl NFT {
         }
     }
 
-    fn get_identity(&self) -> &str {
-        &self.identity
-    }
-
     fn get_conditions(&self) -> &str {
         &self.conditions
     }
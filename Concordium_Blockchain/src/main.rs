@@ -1,253 +0,0 @@
-use concordium_std::*;
-use std::collections::HashSet;
-
-// Structure to hold the attributes that can be revealed
-struct Attributes {
-    first_name: Option<String>,
-    last_name: Option<String>,
-    sex: Option<String>,
-    date_of_birth: Option<String>,
-    country_of_residence: Option<String>,
-    country_of_nationality: Option<String>,
-    id_document_type: Option<String>,
-    id_document_number: Option<String>,
-    id_document_issuer: Option<String>,
-    id_valid_from: Option<String>,
-    id_valid_to: Option<String>,
-    national_id_number: Option<String>,
-    tax_id_number: Option<String>,
-}
-
-#[concordium_contract(storage)]
-pub fn mint_nft(
-    ctx: &impl HasActions,
-    owner: AccountAddress,
-    identity: String,
-    conditions: String,
-) -> (Storage, Event) {
-    let caller = ctx.get_caller();
-
-    // Verify the ownership and identity
-    if caller != owner {
-        return (
-            Storage::new(),
-            Event::new().error("Caller is not the owner of the NFT"),
-        );
-    }
-
-    // Store the NFT details in the contract storage
-    let nft = NFT::new(owner, identity, conditions);
-    let storage = Storage::new().set(&caller, &nft);
-
-
-    (
-        storage,
-        Event::new().info("NFT minted successfully"),
-    )
-}
-
-#[concordium_contract(storage)]
-pub fn buy_nft(
-    ctx: &impl HasActions,
-    owner: AccountAddress,
-) -> (Storage, Event) {
-    let caller = ctx.get_caller();
-
-    // Retrieve the NFT details from the contract storage
-    let storage = Storage::new();
-    let nft: Option<NFT> = storage.get(&owner);
-
-    match nft {
-        Some(nft) => {
-            // Perform the conditions check
-            let conditions = nft.get_conditions();
-            let can_buy = check_conditions(&caller, &conditions);
-
-            if can_buy {
-                // Transfer ownership of the NFT to the caller
-                let new_nft = NFT::new(caller.clone(), nft.get_identity(), conditions);
-                let new_storage = storage.set(&caller, &new_nft).remove(&owner);
-
-                (
-                    new_storage,
-                    Event::new().info("NFT purchased successfully"),
-                )
-            } else {
-                (
-                    storage,
-                    Event::new().error("Conditions not met to purchase the NFT"),
-                )
-            }
-        }
-        None => (
-            storage,
-            Event::new().error("NFT with the specified owner not found"),
-        ),
-    }
-}
-
-fn check_conditions(caller: &AccountAddress, conditions: &str) -> bool {
-    // Perform your custom conditions check here
-    // Example: Allow the caller to buy if they are whitelisted
-    let whitelist = ["0x12345678", "0xabcdef01"];
-    whitelist.contains(&caller.to_hex())
-}
-
-struct NFT {
-    owner: AccountAddress,
-    identity: String,
-    conditions: String,
-}
-
-impl NFT {
-    fn new(owner: AccountAddress, identity: String, conditions: String) -> Self {
-        Self {
-            owner,
-            identity,
-            conditions,
-        }
-    }
-
-    fn get_identity(&self) -> &str {
-        &self.identity
-    }
-
-    fn get_conditions(&self) -> &str {
-        &self.conditions
-    }
-}
-
-#[concordium_contract(storage)]
-pub fn generate_reveal_statement(
-    ctx: &impl HasActions,
-    attributes: Attributes,
-) -> (Storage, Event) {
-    let caller = ctx.get_caller();
-
-    // Construct the reveal statement
-    let statement = construct_reveal_statement(&attributes);
-
-    (
-        Storage::new(),
-        Event::new().data("reveal_statement", statement),
-    )
-}
-
-fn construct_reveal_statement(attributes: &Attributes) -> String {
-    let mut statement = String::new();
-    if let Some(first_name) = &attributes.first_name {
-        statement.push_str(&format!("- First Name: {}\n", first_name));
-    }
-    if let Some(last_name) = &attributes.last_name {
-        statement.push_str(&format!("- Last Name: {}\n", last_name));
-    }
-    if let Some(sex) = &attributes.sex {
-        statement.push_str(&format!("- Sex: {}\n", sex));
-    }
-    if let Some(date_of_birth) = &attributes.date_of_birth {
-        statement.push_str(&format!("- Date of Birth: {}\n", date_of_birth));
-    }
-    if let Some(country_of_residence) = &attributes.country_of_residence {
-        statement.push_str(&format!("- Country of Residence: {}\n", country_of_residence));
-    }
-    if let Some(country_of_nationality) = &attributes.country_of_nationality {
-        statement.push_str(&format!("- Country of Nationality: {}\n", country_of_nationality));
-    }
-    if let Some(id_document_type) = &attributes.id_document_type {
-        statement.push_str(&format!("- ID Document Type: {}\n", id_document_type));
-    }
-    if let Some(id_document_number) = &attributes.id_document_number {
-        statement.push_str(&format!("- ID Document Number: {}\n", id_document_number));
-    }
-    if let Some(id_document_issuer) = &attributes.id_document_issuer {
-        statement.push_str(&format!("- ID Document Issuer: {}\n", id_document_issuer));
-    }
-    if let Some(id_valid_from) = &attributes.id_valid_from {
-        statement.push_str(&format!("- ID Valid From: {}\n", id_valid_from));
-    }
-    if let Some(id_valid_to) = &attributes.id_valid_to {
-        statement.push_str(&format!("- ID Valid To: {}\n", id_valid_to));
-    }
-    if let Some(national_id_number) = &attributes.national_id_number {
-        statement.push_str(&format!("- National ID number: {}\n", national_id_number));
-    }
-    if let Some(tax_id_number) = &attributes.tax_id_number {
-        statement.push_str(&format!("- Tax ID number: {}\n", tax_id_number));
-    }
-    statement
-}
-
-#[concordium_contract(storage)]
-pub fn generate_range_proof(
-    ctx: &impl HasActions,
-    range_start: u32,
-    range_end: u32,
-) -> (Storage, Event) {
-    let caller = ctx.get_caller();
-
-    // Check if the range is valid
-    let is_valid_range = range_start <= range_end;
-
-    if is_valid_range {
-        // Generate the range proof using the specified range attributes
-        let range_proof = generate_range_proof_string(range_start, range_end);
-
-        (
-            Storage::new(),
-            Event::new().data("range_proof", range_proof),
-        )
-    } else {
-        (
-            Storage::new(),
-            Event::new().error("Invalid range provided"),
-        )
-    }
-}
-
-fn generate_range_proof_string(range_start: u32, range_end: u32) -> String {
-    // Generate the range proof string using the specified range
-    // This is a placeholder implementation, adjust it according to your specific range proof generation logic
-    format!("Range Proof: {} - {}", range_start, range_end)
-}
-
-#[concordium_contract(storage)]
-pub fn generate_membership_proof(
-    ctx: &impl HasActions,
-    attributes: Attributes,
-) -> (Storage, Event) {
-    let caller = ctx.get_caller();
-
-    // Check if the caller has one of the required attributes
-    let has_membership = check_membership(&caller, &attributes);
-
-    if has_membership {
-        (
-            Storage::new(),
-            Event::new().info("Caller has the required membership attribute"),
-        )
-    } else {
-        (
-            Storage::new(),
-            Event::new().error("Caller does not have the required membership attribute"),
-        )
-    }
-}
-
-fn check_membership(caller: &AccountAddress, attributes: &Attributes) -> bool {
-    // Perform membership check based on the specified attributes
-    let required_attributes: HashSet<Option<String>> = [
-        attributes.country_of_residence.clone(),
-        attributes.country_of_nationality.clone(),
-        attributes.id_document_type.clone(),
-        attributes.id_document_issuer.clone(),
-    ]
-    .iter()
-    .cloned()
-    .collect();
-
-    // Perform your custom membership check here
-    // Example: Check if the caller has one of the required attributes
-    required_attributes.contains(&Some(caller.to_string()))
-}
-
-
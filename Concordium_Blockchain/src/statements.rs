@@ -0,0 +1,145 @@
+use crate::attributes::{
+    AttributeTag, Attributes, CountryCode, Date, IdDocumentIssuer, IdDocumentType,
+};
+use concordium_std::*;
+
+// Identity statements, rendered in the JSON format that wallets prove and that
+// the id-verifier backend loads as a policy from `--policies`, e.g.
+//
+//   [{"type":"RevealAttribute","attributeTag":"firstName"}]
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum Statement {
+    RevealAttribute(AttributeTag),
+    // The attribute lies in `[lower, upper)`.
+    AttributeInRange {
+        tag: AttributeTag,
+        lower: Date,
+        upper: Date,
+    },
+    AttributeInSet {
+        tag: AttributeTag,
+        set: Vec<String>,
+    },
+    AttributeNotInSet {
+        tag: AttributeTag,
+        set: Vec<String>,
+    },
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum StatementError {
+    // Only date attributes can be proven to lie in a range.
+    NotRangeable,
+    // A bound is not a `YYYYMMDD` date or the range is empty.
+    InvalidBounds,
+    // Only countries, document types and issuers can be checked against a set.
+    NotSettable,
+    // A set is empty or holds a value the attribute can never take.
+    InvalidSetValue,
+}
+
+impl Statement {
+    fn to_json(&self) -> String {
+        match self {
+            Statement::RevealAttribute(tag) => format!(
+                "{{\"type\":\"RevealAttribute\",\"attributeTag\":\"{}\"}}",
+                tag.name()
+            ),
+            Statement::AttributeInRange { tag, lower, upper } => format!(
+                "{{\"type\":\"AttributeInRange\",\"attributeTag\":\"{}\",\"lower\":\"{}\",\"upper\":\"{}\"}}",
+                tag.name(),
+                lower.to_attribute_string(),
+                upper.to_attribute_string()
+            ),
+            Statement::AttributeInSet { tag, set } => set_to_json("AttributeInSet", *tag, set),
+            Statement::AttributeNotInSet { tag, set } => {
+                set_to_json("AttributeNotInSet", *tag, set)
+            }
+        }
+    }
+}
+
+// Set values are validated before they get here, so they need no escaping.
+fn set_to_json(kind: &str, tag: AttributeTag, set: &[String]) -> String {
+    let values: Vec<String> = set.iter().map(|value| format!("\"{}\"", value)).collect();
+    format!(
+        "{{\"type\":\"{}\",\"attributeTag\":\"{}\",\"set\":[{}]}}",
+        kind,
+        tag.name(),
+        values.join(",")
+    )
+}
+
+// Asks for each attribute that is set in `attributes` to be revealed. The
+// values themselves are not part of the statement.
+pub fn reveal_statement(attributes: &Attributes) -> Vec<Statement> {
+    attributes
+        .tags()
+        .into_iter()
+        .map(Statement::RevealAttribute)
+        .collect()
+}
+
+// Asks for a date attribute to lie in `[lower, upper)`, with both bounds
+// given in the `YYYYMMDD` encoding used by identity statements.
+pub fn range_statement(
+    tag: AttributeTag,
+    lower: &str,
+    upper: &str,
+) -> Result<Statement, StatementError> {
+    ensure!(
+        matches!(
+            tag,
+            AttributeTag::DateOfBirth | AttributeTag::IdDocIssuedAt | AttributeTag::IdDocExpiresAt
+        ),
+        StatementError::NotRangeable
+    );
+    let lower = Date::from_attribute_string(lower).map_err(|_| StatementError::InvalidBounds)?;
+    let upper = Date::from_attribute_string(upper).map_err(|_| StatementError::InvalidBounds)?;
+    ensure!(lower < upper, StatementError::InvalidBounds);
+    Ok(Statement::AttributeInRange { tag, lower, upper })
+}
+
+// Asks for an attribute to be (`member`) or not be in `set`. Values use the
+// encoding of identity statements: country codes for countries, `0`-`4` for
+// document types and ISO-3166-1 or ISO-3166-2 codes such as `DK` or `GB-SCT`
+// for document issuers.
+pub fn set_statement(
+    tag: AttributeTag,
+    set: Vec<String>,
+    member: bool,
+) -> Result<Statement, StatementError> {
+    let valid: fn(&str) -> bool = match tag {
+        AttributeTag::CountryOfResidence | AttributeTag::Nationality => {
+            |value| CountryCode::new(value).is_ok()
+        }
+        AttributeTag::IdDocType => |value| {
+            [
+                IdDocumentType::NotApplicable,
+                IdDocumentType::Passport,
+                IdDocumentType::NationalIdCard,
+                IdDocumentType::DrivingLicense,
+                IdDocumentType::ImmigrationCard,
+            ]
+            .iter()
+            .any(|doc_type| doc_type.attribute_value() == value)
+        },
+        AttributeTag::IdDocIssuer => |value| IdDocumentIssuer::new(value).is_ok(),
+        _ => bail!(StatementError::NotSettable),
+    };
+    ensure!(
+        !set.is_empty() && set.iter().all(|value| valid(value)),
+        StatementError::InvalidSetValue
+    );
+    Ok(if member {
+        Statement::AttributeInSet { tag, set }
+    } else {
+        Statement::AttributeNotInSet { tag, set }
+    })
+}
+
+pub fn to_json(statements: &[Statement]) -> String {
+    let entries: Vec<String> = statements.iter().map(Statement::to_json).collect();
+    format!("[{}]", entries.join(","))
+}
@@ -1,125 +1,107 @@
-use concordium_std::*;
-use std::collections::HashSet;
+use super::*;
+use crate::attributes::{Attributes, CountryCode, Date, IdDocumentIssuer, IdDocumentType, Sex};
+use crate::conditions::{Condition, ConditionContext};
 
-// Verifier function for the mint_nft contract
-#[concordium_test]
-fn verify_mint_nft() {
-    let owner = AccountAddress::from_hex("0x01234567").unwrap();
-    let caller = AccountAddress::from_hex("0x01234567").unwrap();
-    let identity = "NFT Identity".to_string();
-    let conditions = "NFT Conditions".to_string();
-
-    let (storage, event) = mint_nft(&TestContext::new(caller), owner.clone(), identity.clone(), conditions.clone());
-
-    assert_eq!(event.data(), "NFT minted successfully");
+// Tests that call entrypoints through the mock host of concordium-std. The
+// mock host is deprecated in favour of concordium-smart-contract-testing, and
+// every use of it warns, not only the import, so the allowance covers the
+// module.
+#[allow(deprecated)]
+mod host;
 
-    let stored_nft: Option<NFT> = storage.get(&owner);
-    assert!(stored_nft.is_some());
+const NFT_OWNER: AccountAddress = AccountAddress([3u8; 32]);
+const NFT_BUYER: AccountAddress = AccountAddress([4u8; 32]);
 
-    let nft = stored_nft.unwrap();
-    assert_eq!(nft.owner, owner);
-    assert_eq!(nft.identity, identity);
-    assert_eq!(nft.conditions, conditions);
-}
-
-// Verifier function for the buy_nft contract
+// Verifier function for the NFT condition language
 #[concordium_test]
-fn verify_buy_nft() {
-    let owner = AccountAddress::from_hex("0x01234567").unwrap();
-    let caller = AccountAddress::from_hex("0x01234567").unwrap();
-
-    let storage = Storage::new().set(&owner, &NFT::new(owner.clone(), "NFT Identity".to_string(), "NFT Conditions".to_string()));
-
-    let (new_storage, event) = buy_nft(&TestContext::new(caller), owner.clone());
-
-    assert_eq!(event.data(), "NFT purchased successfully");
-
-    let stored_nft: Option<NFT> = new_storage.get(&caller);
-    assert!(stored_nft.is_some());
-
-    let nft = stored_nft.unwrap();
-    assert_eq!(nft.owner, caller);
-    assert_eq!(nft.identity, "NFT Identity");
-    assert_eq!(nft.conditions, "NFT Conditions");
-}
-
-// Verifier function for the generate_reveal_statement contract
-#[concordium_test]
-fn verify_generate_reveal_statement() {
-    let attributes = Attributes {
-        first_name: Some("Raj".to_string()),
-        last_name: Some("oberoi".to_string()),
-        sex: Some("Male".to_string()),
-        date_of_birth: Some("1990-01-01".to_string()),
-        country_of_residence: Some("india".to_string()),
-        country_of_nationality: Some("india".to_string()),
-        id_document_type: Some("Passport".to_string()),
-        id_document_number: Some("123456789".to_string()),
-        id_document_issuer: Some("INDIAN Government".to_string()),
-        id_valid_from: Some("2020-01-01".to_string()),
-        id_valid_to: Some("2030-01-01".to_string()),
-        national_id_number: Some("987654321".to_string()),
-        tax_id_number: Some("555-123-4567".to_string()),
+fn verify_check_conditions() {
+    let buyer = NFT_BUYER;
+    let condition = Condition::parse(
+        "allow(0x0303030303030303030303030303030303030303030303030303030303030303) \
+         OR window(10, 20) AND (min_balance(1000) or window(12, 18))",
+    )
+    .unwrap();
+    assert!(condition.needs_balance());
+
+    let mut condition_ctx = ConditionContext {
+        buyer,
+        balance: Some(Amount::from_micro_ccd(999)),
+        slot_time: Timestamp::from_timestamp_millis(15),
     };
+    // AND binds tighter than OR, so the inner window is enough within the outer one
+    assert!(condition.evaluate(&condition_ctx));
 
-    let (storage, event) = generate_reveal_statement(&TestContext::new(AccountAddress::from_hex("0x01234567").unwrap()), attributes.clone());
+    condition_ctx.slot_time = Timestamp::from_timestamp_millis(19);
+    assert!(!condition.evaluate(&condition_ctx));
 
-    let expected_statement = "\
-        - First Name: Raj\n\
-        - Last Name: Oberoi\n\
-        - Sex: Male\n\
-        - Date of Birth: 1990-01-01\n\
-        - Country of Residence: india\n\
-        - Country of Nationality: indian\n\
-        - ID Document Type: Passport\n\
-        - ID Document Number: 123456789\n\
-        - ID Document Issuer: INDIAN Government\n\
-        - ID Valid From: 2020-01-01\n\
-        - ID Valid To: 2030-01-01\n\
-        - National ID number: 987654321\n\
-        - Tax ID number: 555-123-4567\n\
-    ";
-
-    assert_eq!(event.data("reveal_statement").unwrap(), expected_statement);
-    assert_eq!(storage.into_mutation().len(), 0);
-}
+    condition_ctx.balance = Some(Amount::from_micro_ccd(1000));
+    assert!(condition.evaluate(&condition_ctx));
 
-// Verifier function for the generate_range_proof contract
-#[concordium_test]
-fn verify_generate_range_proof() {
-    let range_start = 10;
-    let range_end = 20;
+    condition_ctx.slot_time = Timestamp::from_timestamp_millis(21);
+    assert!(!condition.evaluate(&condition_ctx));
 
-    let (storage, event) = generate_range_proof(&TestContext::new(AccountAddress::from_hex("0x01234567").unwrap()), range_start, range_end);
+    condition_ctx.buyer = NFT_OWNER;
+    assert!(condition.evaluate(&condition_ctx));
 
-    let expected_range_proof = "Range Proof: 10 - 20";
-    assert_eq!(event.data("range_proof").unwrap(), expected_range_proof);
-    assert_eq!(storage.into_mutation().len(), 0);
+    assert_eq!(Condition::parse("  "), Ok(Condition::And(Vec::new())));
+    assert!(Condition::parse("allow()").is_err());
+    assert!(Condition::parse("allow(0x1234)").is_err());
+    assert!(Condition::parse("window(20, 10)").is_err());
+    assert!(Condition::parse("min_balance(1) AND").is_err());
+    assert!(Condition::parse("(min_balance(1)").is_err());
 }
 
-// Verifier function for the generate_membership_proof contract
+// Verifier function for the typed identity attributes
 #[concordium_test]
-fn verify_generate_membership_proof() {
+fn verify_attributes() {
+    // Dates are checked against the calendar
+    assert!(Date::new(2024, 2, 29).is_ok());
+    assert!(Date::new(2023, 2, 29).is_err());
+    assert!(Date::new(2023, 13, 1).is_err());
+    assert_eq!(
+        Date::from_attribute_string("19900101"),
+        Date::new(1990, 1, 1)
+    );
+    assert!(Date::from_attribute_string("1990-01-01").is_err());
+
+    // Dates order chronologically
+    let birth = Date::new(2000, 2, 29).unwrap();
+    assert!(birth < Date::new(2000, 3, 1).unwrap());
+    assert!(birth > Date::new(1999, 12, 31).unwrap());
+
+    assert!(CountryCode::new("DK").is_ok());
+    assert!(CountryCode::new("dk").is_err());
+    assert!(CountryCode::new("XX").is_err());
+
+    // Document issuers are countries, optionally with a subdivision
+    assert!(IdDocumentIssuer::new("DK").is_ok());
+    assert!(IdDocumentIssuer::new("GB-SCT").is_ok());
+    assert!(IdDocumentIssuer::new("gb-sct").is_err());
+    assert!(IdDocumentIssuer::new("GB-").is_err());
+    assert!(IdDocumentIssuer::new("GB-SCOT").is_err());
+    assert!(IdDocumentIssuer::new("XX-SCT").is_err());
+    assert!(IdDocumentIssuer::new("INDIAN Government").is_err());
+
+    // Invalid values are rejected when a parameter is parsed
+    assert!(from_bytes::<Date>(&to_bytes(&(2023u16, 2u8, 29u8))).is_err());
+    assert!(from_bytes::<CountryCode>(&[2, b'D', b'K']).is_ok());
+    assert!(from_bytes::<CountryCode>(&[2, b'X', b'X']).is_err());
+    assert!(from_bytes::<IdDocumentIssuer>(&[6, b'G', b'B', b'-', b'S', b'C', b'T']).is_ok());
+    assert!(from_bytes::<IdDocumentIssuer>(&[3, b'G', b'B', b'-']).is_err());
     let attributes = Attributes {
         first_name: None,
         last_name: None,
-        sex: None,
-        date_of_birth: None,
-        country_of_residence: Some("india".to_string()),
-        country_of_nationality: Some("india".to_string()),
-        id_document_type: Some("Passport".to_string()),
+        sex: Some(Sex::Female),
+        date_of_birth: Some(birth),
+        country_of_residence: Some(CountryCode::new("DK").unwrap()),
+        country_of_nationality: None,
+        id_document_type: Some(IdDocumentType::DrivingLicense),
         id_document_number: None,
-        id_document_issuer: Some("INDIAN Government".to_string()),
+        id_document_issuer: Some(IdDocumentIssuer::new("GB-SCT").unwrap()),
         id_valid_from: None,
         id_valid_to: None,
         national_id_number: None,
         tax_id_number: None,
     };
-
-    let (storage, event) = generate_membership_proof(&TestContext::new(AccountAddress::from_hex("0x01234567").unwrap()), attributes.clone());
-
-    assert_eq!(event.data(), "Caller has the required membership attribute");
-    assert_eq!(storage.into_mutation().len(), 0);
+    assert_eq!(from_bytes(&to_bytes(&attributes)), Ok(attributes));
 }
-
-
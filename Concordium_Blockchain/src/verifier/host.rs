@@ -0,0 +1,1267 @@
+use super::*;
+use crate::attributes::AttributeTag;
+use crate::cis2_contract::{cis2_market, cis2_multi};
+use concordium_cis2::*;
+use concordium_std::test_infrastructure::*;
+
+const NFT_ADMIN: AccountAddress = AccountAddress([2u8; 32]);
+const NFT_VERIFIER_KEY: PublicKeyEd25519 = PublicKeyEd25519([5u8; 32]);
+const NFT_SIGNATURE: SignatureEd25519 = SignatureEd25519([6u8; 64]);
+const NFT_ROTATED_KEY: PublicKeyEd25519 = PublicKeyEd25519([10u8; 32]);
+const NFT_ROTATED_SIGNATURE: SignatureEd25519 = SignatureEd25519([11u8; 64]);
+const NFT_POLICY_HASH: HashSha2256 = HashSha2256([8u8; 32]);
+const NFT_CONTRACT: ContractAddress = ContractAddress {
+    index: 3,
+    subindex: 0,
+};
+// An attestation the id-verifier backend issued for `NFT_BUYER` and the `age18`
+// policy on 20 October 2026 at 09:00 UTC, valid for an hour.
+const BACKEND_KEY: PublicKeyEd25519 = PublicKeyEd25519([
+    11, 81, 58, 217, 180, 146, 64, 21, 202, 9, 2, 237, 7, 144, 68, 211, 172, 93, 190, 194, 48, 111,
+    6, 148, 140, 16, 218, 142, 182, 227, 159, 45,
+]);
+// SHA-256 of `age18`.
+const BACKEND_POLICY_HASH: HashSha2256 = HashSha2256([
+    21, 78, 243, 1, 220, 17, 93, 213, 102, 255, 233, 49, 170, 118, 153, 179, 100, 97, 63, 232, 53,
+    29, 8, 70, 180, 177, 61, 31, 253, 177, 227, 93,
+]);
+const BACKEND_ISSUED_AT: u64 = 1_792_486_800_000;
+const BACKEND_SIGNATURE: SignatureEd25519 = SignatureEd25519([
+    115, 249, 16, 174, 9, 159, 206, 155, 212, 139, 122, 143, 38, 195, 57, 62, 111, 129, 211, 86,
+    56, 87, 252, 211, 201, 2, 100, 32, 107, 250, 25, 242, 38, 73, 160, 37, 151, 145, 27, 11, 54,
+    211, 134, 101, 13, 45, 108, 133, 118, 182, 59, 82, 238, 30, 47, 180, 106, 187, 61, 187, 253,
+    74, 143, 11,
+]);
+
+fn nft_host() -> TestHost<State<TestStateApi>> {
+    let parameter_bytes = to_bytes(&InitParams {
+        verifier_key: VerifierKeyParams {
+            key_id: "key-1".to_string(),
+            key: VerifierKey {
+                key: NFT_VERIFIER_KEY,
+                valid_from: Timestamp::from_timestamp_millis(0),
+                valid_until: None,
+            },
+        },
+        policy_hash: NFT_POLICY_HASH,
+    });
+    let mut init_ctx = TestInitContext::empty();
+    init_ctx.set_init_origin(NFT_ADMIN);
+    init_ctx.set_parameter(&parameter_bytes);
+    let mut state_builder = TestStateBuilder::new();
+    let state = contract_init(&init_ctx, &mut state_builder).unwrap();
+    TestHost::new(state, state_builder)
+}
+
+// Attestation for `NFT_BUYER`, valid until 1000ms.
+fn nft_attestation(contract: Option<ContractAddress>) -> Attestation {
+    Attestation {
+        account: NFT_BUYER,
+        policy_hash: NFT_POLICY_HASH,
+        challenge: [9u8; 32],
+        issued_at: Timestamp::from_timestamp_millis(0),
+        expires_at: Timestamp::from_timestamp_millis(1000),
+        contract,
+        key_id: "key-1".to_string(),
+    }
+}
+
+// `NFT_SIGNATURE` and `NFT_ROTATED_SIGNATURE` stand for the signatures of the
+// two verifier keys on any attestation, so that the checks on its content can
+// be tested on their own.
+fn nft_crypto_primitives() -> TestCryptoPrimitives {
+    let crypto_primitives = TestCryptoPrimitives::new();
+    crypto_primitives.setup_verify_ed25519_signature_mock(|key, signature, _message| {
+        (key == NFT_VERIFIER_KEY && signature == NFT_SIGNATURE)
+            || (key == NFT_ROTATED_KEY && signature == NFT_ROTATED_SIGNATURE)
+    });
+    crypto_primitives
+}
+
+fn nft_buy_params(token_id: TokenId, attestation: Attestation) -> BuyParams {
+    BuyParams {
+        token_id,
+        attestation,
+        signature: NFT_SIGNATURE,
+    }
+}
+
+fn nft_mint(
+    host: &mut TestHost<State<TestStateApi>>,
+    owner: AccountAddress,
+    identity: &str,
+    conditions: &str,
+) -> TokenId {
+    let params = MintParams {
+        owner,
+        identity: identity.to_string(),
+        conditions: conditions.to_string(),
+    };
+    let parameter_bytes = to_bytes(&params);
+    let mut ctx = TestReceiveContext::empty();
+    ctx.set_sender(Address::Account(owner));
+    ctx.set_parameter(&parameter_bytes);
+    let mut logger = TestLogger::init();
+    mint_nft(&ctx, host, &mut logger).unwrap()
+}
+
+fn nft_allowlist(
+    host: &mut TestHost<State<TestStateApi>>,
+    sender: AccountAddress,
+    add: bool,
+    accounts: &[AccountAddress],
+) -> (Result<(), NftError>, TestLogger) {
+    let parameter_bytes = to_bytes(&AllowlistParams {
+        accounts: accounts.to_vec(),
+    });
+    let mut ctx = TestReceiveContext::empty();
+    ctx.set_sender(Address::Account(sender));
+    ctx.set_parameter(&parameter_bytes);
+    let mut logger = TestLogger::init();
+    let result = if add {
+        add_to_allowlist(&ctx, host, &mut logger)
+    } else {
+        remove_from_allowlist(&ctx, host, &mut logger)
+    };
+    (result, logger)
+}
+
+fn nft_is_allowlisted(host: &TestHost<State<TestStateApi>>, account: AccountAddress) -> bool {
+    let parameter_bytes = to_bytes(&account);
+    let mut ctx = TestReceiveContext::empty();
+    ctx.set_parameter(&parameter_bytes);
+    is_allowlisted(&ctx, host).unwrap()
+}
+
+fn nft_tokens_of(host: &TestHost<State<TestStateApi>>, owner: AccountAddress) -> Vec<TokenId> {
+    let parameter_bytes = to_bytes(&owner);
+    let mut ctx = TestReceiveContext::empty();
+    ctx.set_parameter(&parameter_bytes);
+    tokens_of(&ctx, host).unwrap()
+}
+
+// Verifier function for the mint_nft contract
+#[concordium_test]
+fn verify_mint_nft() {
+    let mut host = nft_host();
+
+    // An account can mint and hold several NFTs
+    let first = nft_mint(&mut host, NFT_OWNER, "NFT Identity", "window(0, 1000)");
+    let second = nft_mint(&mut host, NFT_OWNER, "Second NFT Identity", "");
+    assert_ne!(first, second);
+    assert_eq!(nft_tokens_of(&host, NFT_OWNER), [first, second]);
+
+    let nft = host.state().nfts.get(&first).unwrap();
+    assert_eq!(nft.owner, NFT_OWNER);
+    assert_eq!(nft.identity, "NFT Identity");
+    assert_eq!(nft.conditions, "window(0, 1000)");
+
+    // Minting on behalf of another account is rejected
+    let params = MintParams {
+        owner: NFT_OWNER,
+        identity: "NFT Identity".to_string(),
+        conditions: "".to_string(),
+    };
+    let parameter_bytes = to_bytes(&params);
+    let mut ctx = TestReceiveContext::empty();
+    ctx.set_sender(Address::Account(NFT_BUYER));
+    ctx.set_parameter(&parameter_bytes);
+    let mut logger = TestLogger::init();
+    assert_eq!(
+        mint_nft(&ctx, &mut host, &mut logger),
+        Err(NftError::NotOwner)
+    );
+
+    // So is minting with conditions that cannot be parsed
+    let params = MintParams {
+        owner: NFT_OWNER,
+        identity: "NFT Identity".to_string(),
+        conditions: "NFT Conditions".to_string(),
+    };
+    let parameter_bytes = to_bytes(&params);
+    ctx.set_sender(Address::Account(NFT_OWNER));
+    ctx.set_parameter(&parameter_bytes);
+    assert_eq!(
+        mint_nft(&ctx, &mut host, &mut logger),
+        Err(NftError::InvalidConditions)
+    );
+}
+
+// Verifier function for the allowlist entrypoints
+#[concordium_test]
+fn verify_allowlist() {
+    let mut host = nft_host();
+
+    // Only the admin can update the allowlist
+    let (result, _) = nft_allowlist(&mut host, NFT_OWNER, true, &[NFT_OWNER]);
+    assert_eq!(result, Err(NftError::Unauthorized));
+    assert!(!nft_is_allowlisted(&host, NFT_OWNER));
+
+    // Accounts are added in batches, with an event for each new account
+    let (result, logger) = nft_allowlist(&mut host, NFT_ADMIN, true, &[NFT_OWNER, NFT_BUYER]);
+    assert!(result.is_ok());
+    assert_eq!(logger.logs.len(), 2);
+    let (result, logger) = nft_allowlist(&mut host, NFT_ADMIN, true, &[NFT_BUYER]);
+    assert!(result.is_ok());
+    assert!(logger.logs.is_empty());
+    assert!(nft_is_allowlisted(&host, NFT_OWNER));
+    assert!(nft_is_allowlisted(&host, NFT_BUYER));
+
+    let (result, logger) = nft_allowlist(&mut host, NFT_ADMIN, false, &[NFT_BUYER]);
+    assert!(result.is_ok());
+    assert_eq!(
+        logger.logs,
+        [to_bytes(&NftEvent::RemovedFromAllowlist {
+            account: NFT_BUYER
+        })]
+    );
+    assert!(nft_is_allowlisted(&host, NFT_OWNER));
+    assert!(!nft_is_allowlisted(&host, NFT_BUYER));
+}
+
+// Verifier function for the buy_nft contract
+#[concordium_test]
+fn verify_buy_nft() {
+    let mut host = nft_host();
+    let conditions = "allow(0x0404040404040404040404040404040404040404040404040404040404040404)";
+    let held = nft_mint(&mut host, NFT_BUYER, "Held NFT Identity", "");
+    let for_sale = nft_mint(&mut host, NFT_OWNER, "NFT Identity", conditions);
+    let owner_only = nft_mint(
+        &mut host,
+        NFT_OWNER,
+        "Owner NFT Identity",
+        "allow(0x0303030303030303030303030303030303030303030303030303030303030303)",
+    );
+    let open = nft_mint(&mut host, NFT_OWNER, "Open NFT Identity", "");
+
+    let mut ctx = TestReceiveContext::empty();
+    ctx.set_sender(Address::Account(NFT_BUYER));
+    ctx.set_self_address(NFT_CONTRACT);
+    ctx.set_metadata_slot_time(Timestamp::from_timestamp_millis(0));
+    let mut logger = TestLogger::init();
+    let crypto_primitives = nft_crypto_primitives();
+
+    // Only accounts on the contract allowlist can buy
+    let parameter_bytes = to_bytes(&nft_buy_params(for_sale, nft_attestation(None)));
+    ctx.set_parameter(&parameter_bytes);
+    assert_eq!(
+        buy_nft(&ctx, &mut host, &mut logger, &crypto_primitives),
+        Err(NftError::NotAllowlisted)
+    );
+    nft_allowlist(&mut host, NFT_ADMIN, true, &[NFT_BUYER])
+        .0
+        .unwrap();
+
+    // The buyer is not on the allowlist of this NFT
+    let parameter_bytes = to_bytes(&nft_buy_params(owner_only, nft_attestation(None)));
+    ctx.set_parameter(&parameter_bytes);
+    assert_eq!(
+        buy_nft(&ctx, &mut host, &mut logger, &crypto_primitives),
+        Err(NftError::ConditionsNotMet)
+    );
+
+    // A signature the verifier did not issue is rejected
+    let mut params = nft_buy_params(for_sale, nft_attestation(None));
+    params.signature = SignatureEd25519([7u8; 64]);
+    let parameter_bytes = to_bytes(&params);
+    ctx.set_parameter(&parameter_bytes);
+    assert_eq!(
+        buy_nft(&ctx, &mut host, &mut logger, &crypto_primitives),
+        Err(NftError::InvalidAttestation)
+    );
+
+    // Even for an NFT without conditions
+    params.token_id = open;
+    let parameter_bytes = to_bytes(&params);
+    ctx.set_parameter(&parameter_bytes);
+    assert_eq!(
+        buy_nft(&ctx, &mut host, &mut logger, &crypto_primitives),
+        Err(NftError::InvalidAttestation)
+    );
+
+    // So is an attestation scoped to another contract
+    let other_contract = ContractAddress {
+        index: 4,
+        subindex: 0,
+    };
+    let parameter_bytes = to_bytes(&nft_buy_params(
+        for_sale,
+        nft_attestation(Some(other_contract)),
+    ));
+    ctx.set_parameter(&parameter_bytes);
+    assert_eq!(
+        buy_nft(&ctx, &mut host, &mut logger, &crypto_primitives),
+        Err(NftError::InvalidAttestation)
+    );
+
+    // Or one for a different policy than the contract asks for
+    let mut attestation = nft_attestation(None);
+    attestation.policy_hash = HashSha2256([1u8; 32]);
+    let parameter_bytes = to_bytes(&nft_buy_params(for_sale, attestation));
+    ctx.set_parameter(&parameter_bytes);
+    assert_eq!(
+        buy_nft(&ctx, &mut host, &mut logger, &crypto_primitives),
+        Err(NftError::InvalidAttestation)
+    );
+
+    // Or one issued after the current slot time
+    let mut attestation = nft_attestation(None);
+    attestation.issued_at = Timestamp::from_timestamp_millis(1);
+    let parameter_bytes = to_bytes(&nft_buy_params(for_sale, attestation));
+    ctx.set_parameter(&parameter_bytes);
+    assert_eq!(
+        buy_nft(&ctx, &mut host, &mut logger, &crypto_primitives),
+        Err(NftError::AttestationNotYetValid)
+    );
+
+    // And one that has expired
+    let parameter_bytes = to_bytes(&nft_buy_params(
+        for_sale,
+        nft_attestation(Some(NFT_CONTRACT)),
+    ));
+    ctx.set_parameter(&parameter_bytes);
+    ctx.set_metadata_slot_time(Timestamp::from_timestamp_millis(1001));
+    assert_eq!(
+        buy_nft(&ctx, &mut host, &mut logger, &crypto_primitives),
+        Err(NftError::AttestationExpired)
+    );
+
+    ctx.set_metadata_slot_time(Timestamp::from_timestamp_millis(1000));
+    assert!(buy_nft(&ctx, &mut host, &mut logger, &crypto_primitives).is_ok());
+
+    // The purchase is added next to what the buyer already held
+    assert_eq!(nft_tokens_of(&host, NFT_BUYER), [held, for_sale]);
+    assert_eq!(nft_tokens_of(&host, NFT_OWNER), [owner_only, open]);
+
+    let nft = host.state().nfts.get(&for_sale).unwrap();
+    assert_eq!(nft.owner, NFT_BUYER);
+    assert_eq!(nft.identity, "NFT Identity");
+    assert_eq!(nft.conditions, conditions);
+    assert_eq!(host.state().nfts.get(&held).unwrap().owner, NFT_BUYER);
+}
+
+// Verifier function for rotating the verifier keys
+#[concordium_test]
+fn verify_verifier_key_rotation() {
+    let mut host = nft_host();
+    let for_sale = nft_mint(&mut host, NFT_OWNER, "NFT Identity", "");
+    nft_allowlist(&mut host, NFT_ADMIN, true, &[NFT_BUYER])
+        .0
+        .unwrap();
+
+    // Only the admin can add keys
+    let parameter_bytes = to_bytes(&VerifierKeyParams {
+        key_id: "key-2".to_string(),
+        key: VerifierKey {
+            key: NFT_ROTATED_KEY,
+            valid_from: Timestamp::from_timestamp_millis(0),
+            valid_until: None,
+        },
+    });
+    let mut ctx = TestReceiveContext::empty();
+    ctx.set_sender(Address::Account(NFT_OWNER));
+    ctx.set_parameter(&parameter_bytes);
+    ctx.set_metadata_slot_time(Timestamp::from_timestamp_millis(500));
+    let mut logger = TestLogger::init();
+    assert_eq!(
+        add_verifier_key(&ctx, &mut host, &mut logger),
+        Err(NftError::Unauthorized)
+    );
+    ctx.set_sender(Address::Account(NFT_ADMIN));
+    assert!(add_verifier_key(&ctx, &mut host, &mut logger).is_ok());
+    assert_eq!(
+        logger.logs,
+        [to_bytes(&NftEvent::VerifierKeyAdded {
+            key_id: "key-2".to_string(),
+            key: NFT_ROTATED_KEY,
+        })]
+    );
+    // A key id cannot be reused for another key
+    assert_eq!(
+        add_verifier_key(&ctx, &mut host, &mut logger),
+        Err(NftError::VerifierKeyExists)
+    );
+
+    // Retiring the first key stops its signatures from being accepted
+    let parameter_bytes = to_bytes(&"key-1".to_string());
+    ctx.set_parameter(&parameter_bytes);
+    assert!(retire_verifier_key(&ctx, &mut host, &mut logger).is_ok());
+    assert_eq!(
+        host.state()
+            .verifier_keys
+            .get(&"key-1".to_string())
+            .unwrap()
+            .valid_until,
+        Some(Timestamp::from_timestamp_millis(500))
+    );
+
+    let mut buy_ctx = TestReceiveContext::empty();
+    buy_ctx.set_sender(Address::Account(NFT_BUYER));
+    buy_ctx.set_self_address(NFT_CONTRACT);
+    buy_ctx.set_metadata_slot_time(Timestamp::from_timestamp_millis(500));
+    let crypto_primitives = nft_crypto_primitives();
+    let parameter_bytes = to_bytes(&nft_buy_params(for_sale, nft_attestation(None)));
+    buy_ctx.set_parameter(&parameter_bytes);
+    assert_eq!(
+        buy_nft(&buy_ctx, &mut host, &mut logger, &crypto_primitives),
+        Err(NftError::VerifierKeyNotActive)
+    );
+
+    // An attestation names the key that signed it
+    let mut attestation = nft_attestation(None);
+    attestation.key_id = "key-3".to_string();
+    let parameter_bytes = to_bytes(&nft_buy_params(for_sale, attestation));
+    buy_ctx.set_parameter(&parameter_bytes);
+    assert_eq!(
+        buy_nft(&buy_ctx, &mut host, &mut logger, &crypto_primitives),
+        Err(NftError::UnknownVerifierKey)
+    );
+    let mut attestation = nft_attestation(None);
+    attestation.key_id = "key-2".to_string();
+    let parameter_bytes = to_bytes(&nft_buy_params(for_sale, attestation.clone()));
+    buy_ctx.set_parameter(&parameter_bytes);
+    assert_eq!(
+        buy_nft(&buy_ctx, &mut host, &mut logger, &crypto_primitives),
+        Err(NftError::InvalidAttestation)
+    );
+
+    // The rotated key signs attestations in its place
+    let parameter_bytes = to_bytes(&BuyParams {
+        token_id: for_sale,
+        attestation,
+        signature: NFT_ROTATED_SIGNATURE,
+    });
+    buy_ctx.set_parameter(&parameter_bytes);
+    assert!(buy_nft(&buy_ctx, &mut host, &mut logger, &crypto_primitives).is_ok());
+    assert_eq!(host.state().nfts.get(&for_sale).unwrap().owner, NFT_BUYER);
+}
+
+// Verifier function for attestations signed by the id-verifier backend
+#[concordium_test]
+fn verify_backend_attestation() {
+    // The contract is set up weeks before the attestation is issued. The dates of
+    // birth the `age18` statement asks for have moved on since, its name has not.
+    let parameter_bytes = to_bytes(&InitParams {
+        verifier_key: VerifierKeyParams {
+            key_id: "key-1".to_string(),
+            key: VerifierKey {
+                key: BACKEND_KEY,
+                valid_from: Timestamp::from_timestamp_millis(0),
+                valid_until: None,
+            },
+        },
+        policy_hash: BACKEND_POLICY_HASH,
+    });
+    let mut init_ctx = TestInitContext::empty();
+    init_ctx.set_init_origin(NFT_ADMIN);
+    init_ctx.set_parameter(&parameter_bytes);
+    init_ctx.set_metadata_slot_time(Timestamp::from_timestamp_millis(1_790_000_000_000));
+    let mut state_builder = TestStateBuilder::new();
+    let state = contract_init(&init_ctx, &mut state_builder).unwrap();
+    let mut host = TestHost::new(state, state_builder);
+    let for_sale = nft_mint(&mut host, NFT_OWNER, "NFT Identity", "");
+    nft_allowlist(&mut host, NFT_ADMIN, true, &[NFT_BUYER])
+        .0
+        .unwrap();
+
+    // The signature is checked for real, over the bytes the backend signed
+    let crypto_primitives = TestCryptoPrimitives::new();
+    crypto_primitives.setup_verify_ed25519_signature_mock(|key, signature, message| {
+        let key = ed25519_dalek::PublicKey::from_bytes(&key.0).unwrap();
+        let signature = ed25519_dalek::Signature::from_bytes(&signature.0).unwrap();
+        ed25519_dalek::Verifier::verify(&key, message, &signature).is_ok()
+    });
+    let attestation = Attestation {
+        account: NFT_BUYER,
+        policy_hash: BACKEND_POLICY_HASH,
+        challenge: [9u8; 32],
+        issued_at: Timestamp::from_timestamp_millis(BACKEND_ISSUED_AT),
+        expires_at: Timestamp::from_timestamp_millis(BACKEND_ISSUED_AT + 3_600_000),
+        contract: None,
+        key_id: "key-1".to_string(),
+    };
+    let mut ctx = TestReceiveContext::empty();
+    ctx.set_sender(Address::Account(NFT_BUYER));
+    ctx.set_self_address(NFT_CONTRACT);
+    ctx.set_metadata_slot_time(Timestamp::from_timestamp_millis(BACKEND_ISSUED_AT + 60_000));
+    let mut logger = TestLogger::init();
+
+    // Any change to the attestation breaks the signature
+    let mut tampered = attestation.clone();
+    tampered.challenge = [10u8; 32];
+    let parameter_bytes = to_bytes(&BuyParams {
+        token_id: for_sale,
+        attestation: tampered,
+        signature: BACKEND_SIGNATURE,
+    });
+    ctx.set_parameter(&parameter_bytes);
+    assert_eq!(
+        buy_nft(&ctx, &mut host, &mut logger, &crypto_primitives),
+        Err(NftError::InvalidAttestation)
+    );
+
+    let parameter_bytes = to_bytes(&BuyParams {
+        token_id: for_sale,
+        attestation,
+        signature: BACKEND_SIGNATURE,
+    });
+    ctx.set_parameter(&parameter_bytes);
+    assert!(buy_nft(&ctx, &mut host, &mut logger, &crypto_primitives).is_ok());
+    assert_eq!(host.state().nfts.get(&for_sale).unwrap().owner, NFT_BUYER);
+}
+
+// Verifier function for the generate_reveal_statement contract
+#[concordium_test]
+fn verify_generate_reveal_statement() {
+    let attributes = Attributes {
+        first_name: Some("Raj".to_string()),
+        last_name: Some("oberoi".to_string()),
+        sex: Some(Sex::Male),
+        date_of_birth: Some(Date::new(1990, 1, 1).unwrap()),
+        country_of_residence: Some(CountryCode::new("IN").unwrap()),
+        country_of_nationality: Some(CountryCode::new("IN").unwrap()),
+        id_document_type: Some(IdDocumentType::Passport),
+        id_document_number: Some("123456789".to_string()),
+        id_document_issuer: Some(IdDocumentIssuer::new("IN").unwrap()),
+        id_valid_from: Some(Date::new(2020, 1, 1).unwrap()),
+        id_valid_to: Some(Date::new(2030, 1, 1).unwrap()),
+        national_id_number: Some("987654321".to_string()),
+        tax_id_number: Some("555-123-4567".to_string()),
+    };
+
+    let host = nft_host();
+    let parameter_bytes = to_bytes(&attributes);
+    let mut ctx = TestReceiveContext::empty();
+    ctx.set_parameter(&parameter_bytes);
+
+    let expected_statement = concat!(
+        "[",
+        r#"{"type":"RevealAttribute","attributeTag":"firstName"},"#,
+        r#"{"type":"RevealAttribute","attributeTag":"lastName"},"#,
+        r#"{"type":"RevealAttribute","attributeTag":"sex"},"#,
+        r#"{"type":"RevealAttribute","attributeTag":"dob"},"#,
+        r#"{"type":"RevealAttribute","attributeTag":"countryOfResidence"},"#,
+        r#"{"type":"RevealAttribute","attributeTag":"nationality"},"#,
+        r#"{"type":"RevealAttribute","attributeTag":"idDocType"},"#,
+        r#"{"type":"RevealAttribute","attributeTag":"idDocNo"},"#,
+        r#"{"type":"RevealAttribute","attributeTag":"idDocIssuer"},"#,
+        r#"{"type":"RevealAttribute","attributeTag":"idDocIssuedAt"},"#,
+        r#"{"type":"RevealAttribute","attributeTag":"idDocExpiresAt"},"#,
+        r#"{"type":"RevealAttribute","attributeTag":"nationalIdNo"},"#,
+        r#"{"type":"RevealAttribute","attributeTag":"taxIdNo"}"#,
+        "]"
+    );
+    assert_eq!(
+        generate_reveal_statement(&ctx, &host).unwrap(),
+        expected_statement
+    );
+
+    // Only the attributes that are set are asked for
+    let attributes = Attributes {
+        first_name: None,
+        last_name: None,
+        sex: None,
+        date_of_birth: None,
+        country_of_residence: None,
+        country_of_nationality: None,
+        id_document_type: None,
+        id_document_number: None,
+        id_document_issuer: None,
+        id_valid_from: None,
+        national_id_number: None,
+        tax_id_number: None,
+        ..attributes
+    };
+    let parameter_bytes = to_bytes(&attributes);
+    ctx.set_parameter(&parameter_bytes);
+    assert_eq!(
+        generate_reveal_statement(&ctx, &host).unwrap(),
+        r#"[{"type":"RevealAttribute","attributeTag":"idDocExpiresAt"}]"#
+    );
+
+    // A statement that reveals nothing is rejected
+    let attributes = Attributes {
+        id_valid_to: None,
+        ..attributes
+    };
+    let parameter_bytes = to_bytes(&attributes);
+    ctx.set_parameter(&parameter_bytes);
+    assert_eq!(
+        generate_reveal_statement(&ctx, &host),
+        Err(NftError::InvalidStatement)
+    );
+}
+
+fn range_statement(
+    host: &TestHost<State<TestStateApi>>,
+    attribute_tag: AttributeTag,
+    lower: &str,
+    upper: &str,
+) -> Result<String, NftError> {
+    let parameter_bytes = to_bytes(&RangeParams {
+        attribute_tag,
+        lower: lower.to_string(),
+        upper: upper.to_string(),
+    });
+    let mut ctx = TestReceiveContext::empty();
+    ctx.set_parameter(&parameter_bytes);
+    generate_range_statement(&ctx, host)
+}
+
+// Verifier function for the generate_range_statement contract
+#[concordium_test]
+fn verify_generate_range_statement() {
+    let host = nft_host();
+
+    assert_eq!(
+        range_statement(&host, AttributeTag::DateOfBirth, "19000327", "20050327").unwrap(),
+        r#"[{"type":"AttributeInRange","attributeTag":"dob","lower":"19000327","upper":"20050327"}]"#
+    );
+    assert!(range_statement(&host, AttributeTag::IdDocExpiresAt, "20240101", "99991231").is_ok());
+
+    // Only dates can be ranged
+    assert_eq!(
+        range_statement(&host, AttributeTag::FirstName, "19000327", "20050327"),
+        Err(NftError::AttributeNotRangeable)
+    );
+
+    // Bounds must be valid dates in order
+    for (lower, upper) in [
+        ("1900-03-27", "20050327"),
+        ("19000327", "20050230"),
+        ("10", "20"),
+        ("20050327", "20050327"),
+        ("20050327", "19000327"),
+    ] {
+        assert_eq!(
+            range_statement(&host, AttributeTag::DateOfBirth, lower, upper),
+            Err(NftError::InvalidRangeBounds)
+        );
+    }
+}
+
+fn nft_set_attribute_set(
+    host: &mut TestHost<State<TestStateApi>>,
+    name: &str,
+    attribute_tag: AttributeTag,
+    values: &[&str],
+) -> Result<(), NftError> {
+    let parameter_bytes = to_bytes(&AttributeSetParams {
+        name: name.to_string(),
+        set: AttributeSet {
+            attribute_tag,
+            values: values.iter().map(|value| value.to_string()).collect(),
+        },
+    });
+    let mut ctx = TestReceiveContext::empty();
+    ctx.set_sender(Address::Account(NFT_ADMIN));
+    ctx.set_parameter(&parameter_bytes);
+    let mut logger = TestLogger::init();
+    set_attribute_set(&ctx, host, &mut logger)
+}
+
+fn membership_statement(
+    host: &TestHost<State<TestStateApi>>,
+    set_name: &str,
+    member: bool,
+) -> Result<String, NftError> {
+    let parameter_bytes = to_bytes(&MembershipParams {
+        set_name: set_name.to_string(),
+        member,
+    });
+    let mut ctx = TestReceiveContext::empty();
+    ctx.set_parameter(&parameter_bytes);
+    generate_membership_statement(&ctx, host)
+}
+
+// Verifier function for the generate_membership_statement contract
+#[concordium_test]
+fn verify_generate_membership_statement() {
+    let mut host = nft_host();
+    nft_set_attribute_set(
+        &mut host,
+        "eu",
+        AttributeTag::Nationality,
+        &["AT", "BE", "DK"],
+    )
+    .unwrap();
+    nft_set_attribute_set(
+        &mut host,
+        "sanctioned",
+        AttributeTag::CountryOfResidence,
+        &["KP"],
+    )
+    .unwrap();
+
+    assert_eq!(
+        membership_statement(&host, "eu", true).unwrap(),
+        r#"[{"type":"AttributeInSet","attributeTag":"nationality","set":["AT","BE","DK"]}]"#
+    );
+    assert_eq!(
+        membership_statement(&host, "sanctioned", false).unwrap(),
+        r#"[{"type":"AttributeNotInSet","attributeTag":"countryOfResidence","set":["KP"]}]"#
+    );
+    assert_eq!(
+        membership_statement(&host, "unknown", true),
+        Err(NftError::AttributeSetNotFound)
+    );
+
+    // Sets are validated when they are stored
+    assert_eq!(
+        nft_set_attribute_set(&mut host, "names", AttributeTag::FirstName, &["Raj"]),
+        Err(NftError::AttributeNotSettable)
+    );
+    assert_eq!(
+        nft_set_attribute_set(&mut host, "eu", AttributeTag::Nationality, &["india"]),
+        Err(NftError::InvalidSetValues)
+    );
+    assert_eq!(
+        nft_set_attribute_set(&mut host, "eu", AttributeTag::Nationality, &[]),
+        Err(NftError::InvalidSetValues)
+    );
+    assert!(nft_set_attribute_set(&mut host, "docs", AttributeTag::IdDocType, &["1", "2"]).is_ok());
+    assert!(nft_set_attribute_set(
+        &mut host,
+        "issuers",
+        AttributeTag::IdDocIssuer,
+        &["DK", "GB-SCT"]
+    )
+    .is_ok());
+
+    // Only the admin can update sets, and updates replace the stored values
+    let parameter_bytes = to_bytes(&AttributeSetParams {
+        name: "eu".to_string(),
+        set: AttributeSet {
+            attribute_tag: AttributeTag::Nationality,
+            values: vec!["DE".to_string()],
+        },
+    });
+    let mut ctx = TestReceiveContext::empty();
+    ctx.set_sender(Address::Account(NFT_OWNER));
+    ctx.set_parameter(&parameter_bytes);
+    let mut logger = TestLogger::init();
+    assert_eq!(
+        set_attribute_set(&ctx, &mut host, &mut logger),
+        Err(NftError::Unauthorized)
+    );
+    nft_set_attribute_set(&mut host, "eu", AttributeTag::Nationality, &["DE"]).unwrap();
+    assert_eq!(
+        membership_statement(&host, "eu", true).unwrap(),
+        r#"[{"type":"AttributeInSet","attributeTag":"nationality","set":["DE"]}]"#
+    );
+
+    let parameter_bytes = to_bytes(&"eu".to_string());
+    ctx.set_sender(Address::Account(NFT_ADMIN));
+    ctx.set_parameter(&parameter_bytes);
+    assert!(remove_attribute_set(&ctx, &mut host, &mut logger).is_ok());
+    assert_eq!(
+        membership_statement(&host, "eu", true),
+        Err(NftError::AttributeSetNotFound)
+    );
+}
+
+const CIS2_OWNER: AccountAddress = AccountAddress([0u8; 32]);
+const CIS2_HOLDER: AccountAddress = AccountAddress([1u8; 32]);
+const CIS2_OPERATOR: AccountAddress = AccountAddress([2u8; 32]);
+
+// Deploys `cis2_multi` owned by `CIS2_OWNER` and mints 10 units of token 1 to
+// `CIS2_HOLDER`.
+fn cis2_host_with_minted_token() -> TestHost<cis2_multi::State<TestStateApi>> {
+    let mut init_ctx = TestInitContext::empty();
+    init_ctx.set_init_origin(CIS2_OWNER);
+    let mut state_builder = TestStateBuilder::new();
+    let state = cis2_multi::cis2_multi_contract(&init_ctx, &mut state_builder).unwrap();
+    let mut host = TestHost::new(state, state_builder);
+
+    let params = cis2_multi::MintParams {
+        owner: Address::Account(CIS2_HOLDER),
+        token_id: TokenIdU32(1),
+        amount: TokenAmountU64(10),
+        metadata_url: MetadataUrl {
+            url: "https://example.com/nft/1".to_string(),
+            hash: None,
+        },
+    };
+    let parameter_bytes = to_bytes(&params);
+    let mut ctx = TestReceiveContext::empty();
+    ctx.set_sender(Address::Account(CIS2_OWNER));
+    ctx.set_parameter(&parameter_bytes);
+    let mut logger = TestLogger::init();
+    cis2_multi::contract_mint(&ctx, &mut host, &mut logger).unwrap();
+    host
+}
+
+fn cis2_balance_of(
+    host: &TestHost<cis2_multi::State<TestStateApi>>,
+    owner: AccountAddress,
+) -> TokenAmountU64 {
+    let params = BalanceOfQueryParams {
+        queries: vec![BalanceOfQuery {
+            token_id: TokenIdU32(1),
+            address: Address::Account(owner),
+        }],
+    };
+    let parameter_bytes = to_bytes(&params);
+    let mut ctx = TestReceiveContext::empty();
+    ctx.set_parameter(&parameter_bytes);
+    let BalanceOfQueryResponse(balances) = cis2_multi::contract_balance_of(&ctx, host).unwrap();
+    balances[0]
+}
+
+// Verifier function for the cis2_multi mint entrypoint
+#[concordium_test]
+fn verify_cis2_mint() {
+    let mut host = cis2_host_with_minted_token();
+    assert_eq!(cis2_balance_of(&host, CIS2_HOLDER), TokenAmountU64(10));
+
+    let params = cis2_multi::MintParams {
+        owner: Address::Account(CIS2_HOLDER),
+        token_id: TokenIdU32(1),
+        amount: TokenAmountU64(1),
+        metadata_url: MetadataUrl {
+            url: "https://example.com/nft/1".to_string(),
+            hash: None,
+        },
+    };
+    let parameter_bytes = to_bytes(&params);
+    let mut ctx = TestReceiveContext::empty();
+    ctx.set_parameter(&parameter_bytes);
+    let mut logger = TestLogger::init();
+
+    // Only the owner of the contract can mint.
+    ctx.set_sender(Address::Account(CIS2_HOLDER));
+    assert_eq!(
+        cis2_multi::contract_mint(&ctx, &mut host, &mut logger),
+        Err(cis2_multi::ContractError::Unauthorized)
+    );
+
+    // Minting more of a token adds to the balance. Its metadata was already
+    // logged when it was first minted.
+    ctx.set_sender(Address::Account(CIS2_OWNER));
+    assert!(cis2_multi::contract_mint(&ctx, &mut host, &mut logger).is_ok());
+    assert_eq!(cis2_balance_of(&host, CIS2_HOLDER), TokenAmountU64(11));
+    assert_eq!(logger.logs.len(), 1);
+    assert_eq!(
+        logger.logs[0],
+        to_bytes(&Cis2Event::Mint(MintEvent {
+            token_id: TokenIdU32(1),
+            amount: TokenAmountU64(1),
+            owner: Address::Account(CIS2_HOLDER),
+        }))
+    );
+}
+
+// Verifier function for the cis2_multi transfer and updateOperator entrypoints
+#[concordium_test]
+fn verify_cis2_transfer() {
+    let mut host = cis2_host_with_minted_token();
+
+    let transfer = TransferParams::from(vec![Transfer {
+        token_id: TokenIdU32(1),
+        amount: TokenAmountU64(4),
+        from: Address::Account(CIS2_HOLDER),
+        to: Receiver::from_account(CIS2_OPERATOR),
+        data: AdditionalData::empty(),
+    }]);
+    let transfer_bytes = to_bytes(&transfer);
+    let mut ctx = TestReceiveContext::empty();
+    ctx.set_parameter(&transfer_bytes);
+    let mut logger = TestLogger::init();
+
+    // The operator cannot move tokens before being added by the holder.
+    ctx.set_sender(Address::Account(CIS2_OPERATOR));
+    assert_eq!(
+        cis2_multi::contract_transfer(&ctx, &mut host, &mut logger),
+        Err(cis2_multi::ContractError::Unauthorized)
+    );
+
+    let update = UpdateOperatorParams(vec![UpdateOperator {
+        update: OperatorUpdate::Add,
+        operator: Address::Account(CIS2_OPERATOR),
+    }]);
+    let update_bytes = to_bytes(&update);
+    let mut update_ctx = TestReceiveContext::empty();
+    update_ctx.set_sender(Address::Account(CIS2_HOLDER));
+    update_ctx.set_parameter(&update_bytes);
+    assert!(cis2_multi::contract_update_operator(&update_ctx, &mut host, &mut logger).is_ok());
+
+    assert!(cis2_multi::contract_transfer(&ctx, &mut host, &mut logger).is_ok());
+    assert_eq!(cis2_balance_of(&host, CIS2_HOLDER), TokenAmountU64(6));
+    assert_eq!(cis2_balance_of(&host, CIS2_OPERATOR), TokenAmountU64(4));
+}
+
+// Verifier function for the cis2_multi operatorOf entrypoint
+#[concordium_test]
+fn verify_cis2_operator_of() {
+    let mut host = cis2_host_with_minted_token();
+    let query = OperatorOfQueryParams {
+        queries: vec![
+            OperatorOfQuery {
+                owner: Address::Account(CIS2_HOLDER),
+                address: Address::Account(CIS2_OPERATOR),
+            },
+            OperatorOfQuery {
+                owner: Address::Account(CIS2_OPERATOR),
+                address: Address::Account(CIS2_HOLDER),
+            },
+        ],
+    };
+    let query_bytes = to_bytes(&query);
+    let mut query_ctx = TestReceiveContext::empty();
+    query_ctx.set_parameter(&query_bytes);
+    assert_eq!(
+        cis2_multi::contract_operator_of(&query_ctx, &host),
+        Ok(OperatorOfQueryResponse(vec![false, false]))
+    );
+
+    // Operators are per owner, so adding one does not work the other way round.
+    let update = UpdateOperatorParams(vec![UpdateOperator {
+        update: OperatorUpdate::Add,
+        operator: Address::Account(CIS2_OPERATOR),
+    }]);
+    let update_bytes = to_bytes(&update);
+    let mut ctx = TestReceiveContext::empty();
+    ctx.set_sender(Address::Account(CIS2_HOLDER));
+    ctx.set_parameter(&update_bytes);
+    let mut logger = TestLogger::init();
+    assert!(cis2_multi::contract_update_operator(&ctx, &mut host, &mut logger).is_ok());
+    assert_eq!(
+        cis2_multi::contract_operator_of(&query_ctx, &host),
+        Ok(OperatorOfQueryResponse(vec![true, false]))
+    );
+}
+
+// Verifier function for the cis2_multi tokenMetadata entrypoint
+#[concordium_test]
+fn verify_cis2_token_metadata() {
+    let host = cis2_host_with_minted_token();
+    let query_bytes = to_bytes(&TokenMetadataQueryParams {
+        queries: vec![TokenIdU32(1)],
+    });
+    let mut ctx = TestReceiveContext::empty();
+    ctx.set_parameter(&query_bytes);
+    let TokenMetadataQueryResponse(urls) =
+        cis2_multi::contract_token_metadata(&ctx, &host).unwrap();
+    assert_eq!(urls.len(), 1);
+    assert_eq!(urls[0].url, "https://example.com/nft/1");
+    assert_eq!(urls[0].hash, None);
+
+    // Tokens that were never minted have no metadata.
+    let query_bytes = to_bytes(&TokenMetadataQueryParams {
+        queries: vec![TokenIdU32(1), TokenIdU32(2)],
+    });
+    ctx.set_parameter(&query_bytes);
+    assert_eq!(
+        cis2_multi::contract_token_metadata(&ctx, &host).map(|_| ()),
+        Err(cis2_multi::ContractError::InvalidTokenId)
+    );
+}
+
+// Verifier function for the cis2_multi supports and setImplementors entrypoints
+#[concordium_test]
+fn verify_cis2_supports() {
+    let mut host = cis2_host_with_minted_token();
+    let standard = |id: &str| StandardIdentifierOwned::new_unchecked(id.to_string());
+    let query_bytes = to_bytes(&SupportsQueryParams {
+        queries: vec![standard("CIS-0"), standard("CIS-2"), standard("CIS-3")],
+    });
+    let mut query_ctx = TestReceiveContext::empty();
+    query_ctx.set_parameter(&query_bytes);
+    assert_eq!(
+        to_bytes(&cis2_multi::contract_supports(&query_ctx, &host).unwrap()),
+        to_bytes(&SupportsQueryResponse::from(vec![
+            SupportResult::Support,
+            SupportResult::Support,
+            SupportResult::NoSupport,
+        ]))
+    );
+
+    // Only the owner can point other standards at their implementors.
+    let implementor = ContractAddress {
+        index: 7,
+        subindex: 0,
+    };
+    let params_bytes = to_bytes(&cis2_multi::SetImplementorsParams {
+        id: standard("CIS-3"),
+        implementors: vec![implementor],
+    });
+    let mut ctx = TestReceiveContext::empty();
+    ctx.set_sender(Address::Account(CIS2_HOLDER));
+    ctx.set_parameter(&params_bytes);
+    assert_eq!(
+        cis2_multi::contract_set_implementor(&ctx, &mut host),
+        Err(cis2_multi::ContractError::Unauthorized)
+    );
+    ctx.set_sender(Address::Account(CIS2_OWNER));
+    assert!(cis2_multi::contract_set_implementor(&ctx, &mut host).is_ok());
+    assert_eq!(
+        to_bytes(&cis2_multi::contract_supports(&query_ctx, &host).unwrap()),
+        to_bytes(&SupportsQueryResponse::from(vec![
+            SupportResult::Support,
+            SupportResult::Support,
+            SupportResult::SupportBy(vec![implementor]),
+        ]))
+    );
+}
+
+const MARKET_TOKEN_CONTRACT: ContractAddress = ContractAddress {
+    index: 1,
+    subindex: 0,
+};
+const MARKET_ADDRESS: ContractAddress = ContractAddress {
+    index: 2,
+    subindex: 0,
+};
+
+// Deploys `cis2_market` next to a mocked CIS-2 contract in which the market is
+// an operator of every account and each account holds 5 units of each token.
+fn market_host() -> TestHost<cis2_market::State<TestStateApi>> {
+    let mut state_builder = TestStateBuilder::new();
+    let state =
+        cis2_market::cis2_market_contract(&TestInitContext::empty(), &mut state_builder).unwrap();
+    let mut host = TestHost::new(state, state_builder);
+    host.setup_mock_entrypoint(
+        MARKET_TOKEN_CONTRACT,
+        OwnedEntrypointName::new_unchecked("operatorOf".to_string()),
+        MockFn::new_v1(|_, _, _, _| Ok((false, OperatorOfQueryResponse(vec![true])))),
+    );
+    host.setup_mock_entrypoint(
+        MARKET_TOKEN_CONTRACT,
+        OwnedEntrypointName::new_unchecked("balanceOf".to_string()),
+        MockFn::new_v1(|_, _, _, _| Ok((false, BalanceOfQueryResponse(vec![TokenAmountU64(5)])))),
+    );
+    host.setup_mock_entrypoint(
+        MARKET_TOKEN_CONTRACT,
+        OwnedEntrypointName::new_unchecked("transfer".to_string()),
+        MockFn::new_v1(|_, _, _, _| Ok((true, ()))),
+    );
+    host
+}
+
+fn market_list(host: &mut TestHost<cis2_market::State<TestStateApi>>, quantity: u64) {
+    let params = cis2_market::ListParams {
+        contract: MARKET_TOKEN_CONTRACT,
+        token_id: TokenIdVec(vec![1]),
+        price: Amount::from_ccd(10),
+        quantity: TokenAmountU64(quantity),
+    };
+    let parameter_bytes = to_bytes(&params);
+    let mut ctx = TestReceiveContext::empty();
+    ctx.set_sender(Address::Account(CIS2_HOLDER));
+    ctx.set_self_address(MARKET_ADDRESS);
+    ctx.set_parameter(&parameter_bytes);
+    let mut logger = TestLogger::init();
+    cis2_market::contract_list(&ctx, host, &mut logger).unwrap();
+}
+
+// Verifier function for the cis2_market list and view entrypoints
+#[concordium_test]
+fn verify_cis2_market_list() {
+    let mut host = market_host();
+    market_list(&mut host, 3);
+
+    let params = cis2_market::ViewParams { skip: 0, take: 10 };
+    let parameter_bytes = to_bytes(&params);
+    let mut ctx = TestReceiveContext::empty();
+    ctx.set_parameter(&parameter_bytes);
+    let view = cis2_market::contract_view(&ctx, &host).unwrap();
+    assert_eq!(view.listings.len(), 1);
+    assert_eq!(view.listings[0].key.owner, CIS2_HOLDER);
+    assert_eq!(view.listings[0].listing.price, Amount::from_ccd(10));
+
+    // Listing more than the holder owns is rejected.
+    let params = cis2_market::ListParams {
+        contract: MARKET_TOKEN_CONTRACT,
+        token_id: TokenIdVec(vec![2]),
+        price: Amount::from_ccd(10),
+        quantity: TokenAmountU64(6),
+    };
+    let parameter_bytes = to_bytes(&params);
+    let mut ctx = TestReceiveContext::empty();
+    ctx.set_sender(Address::Account(CIS2_HOLDER));
+    ctx.set_self_address(MARKET_ADDRESS);
+    ctx.set_parameter(&parameter_bytes);
+    let mut logger = TestLogger::init();
+    assert_eq!(
+        cis2_market::contract_list(&ctx, &mut host, &mut logger),
+        Err(cis2_market::MarketError::InsufficientBalance)
+    );
+}
+
+// Verifier function for the cis2_market view entrypoint clamping `take`
+#[concordium_test]
+fn verify_cis2_market_view_clamps_take() {
+    let mut host = market_host();
+    for id in 0..=cis2_market::MAX_VIEW_TAKE {
+        let params = cis2_market::ListParams {
+            contract: MARKET_TOKEN_CONTRACT,
+            token_id: TokenIdVec(id.to_le_bytes().to_vec()),
+            price: Amount::from_ccd(10),
+            quantity: TokenAmountU64(1),
+        };
+        let parameter_bytes = to_bytes(&params);
+        let mut ctx = TestReceiveContext::empty();
+        ctx.set_sender(Address::Account(CIS2_HOLDER));
+        ctx.set_self_address(MARKET_ADDRESS);
+        ctx.set_parameter(&parameter_bytes);
+        let mut logger = TestLogger::init();
+        cis2_market::contract_list(&ctx, &mut host, &mut logger).unwrap();
+    }
+
+    let params = cis2_market::ViewParams {
+        skip: 0,
+        take: u32::MAX,
+    };
+    let parameter_bytes = to_bytes(&params);
+    let mut ctx = TestReceiveContext::empty();
+    ctx.set_parameter(&parameter_bytes);
+    let view = cis2_market::contract_view(&ctx, &host).unwrap();
+    assert_eq!(view.listings.len(), cis2_market::MAX_VIEW_TAKE as usize);
+
+    // The remaining listing is reached by skipping past the first page.
+    let params = cis2_market::ViewParams {
+        skip: cis2_market::MAX_VIEW_TAKE,
+        take: u32::MAX,
+    };
+    let parameter_bytes = to_bytes(&params);
+    let mut ctx = TestReceiveContext::empty();
+    ctx.set_parameter(&parameter_bytes);
+    let view = cis2_market::contract_view(&ctx, &host).unwrap();
+    assert_eq!(view.listings.len(), 1);
+}
+
+// Verifier function for the cis2_market buy entrypoint
+#[concordium_test]
+fn verify_cis2_market_buy() {
+    let mut host = market_host();
+    market_list(&mut host, 3);
+
+    let params = cis2_market::BuyParams {
+        contract: MARKET_TOKEN_CONTRACT,
+        token_id: TokenIdVec(vec![1]),
+        owner: CIS2_HOLDER,
+        quantity: TokenAmountU64(2),
+    };
+    let parameter_bytes = to_bytes(&params);
+    let mut ctx = TestReceiveContext::empty();
+    ctx.set_sender(Address::Account(CIS2_OPERATOR));
+    ctx.set_parameter(&parameter_bytes);
+    let mut logger = TestLogger::init();
+
+    // Paying less than the listed price fails.
+    assert_eq!(
+        cis2_market::contract_buy(&ctx, &mut host, Amount::from_ccd(10), &mut logger),
+        Err(cis2_market::MarketError::InvalidAmountPaid)
+    );
+
+    host.set_self_balance(Amount::from_ccd(20));
+    assert!(cis2_market::contract_buy(&ctx, &mut host, Amount::from_ccd(20), &mut logger).is_ok());
+    assert_eq!(host.get_transfers(), [(CIS2_HOLDER, Amount::from_ccd(20))]);
+
+    let params = cis2_market::ViewParams { skip: 0, take: 10 };
+    let parameter_bytes = to_bytes(&params);
+    let mut ctx = TestReceiveContext::empty();
+    ctx.set_parameter(&parameter_bytes);
+    let view = cis2_market::contract_view(&ctx, &host).unwrap();
+    assert_eq!(view.listings[0].listing.quantity, TokenAmountU64(1));
+}
+
+fn market_view(host: &TestHost<cis2_market::State<TestStateApi>>) -> cis2_market::ListingsView {
+    let parameter_bytes = to_bytes(&cis2_market::ViewParams { skip: 0, take: 10 });
+    let mut ctx = TestReceiveContext::empty();
+    ctx.set_parameter(&parameter_bytes);
+    cis2_market::contract_view(&ctx, host).unwrap()
+}
+
+// Verifier function for the cis2_market updatePrice entrypoint
+#[concordium_test]
+fn verify_cis2_market_update_price() {
+    let mut host = market_host();
+    market_list(&mut host, 3);
+
+    let params = cis2_market::UpdatePriceParams {
+        contract: MARKET_TOKEN_CONTRACT,
+        token_id: TokenIdVec(vec![1]),
+        price: Amount::from_ccd(15),
+    };
+    let parameter_bytes = to_bytes(&params);
+    let mut ctx = TestReceiveContext::empty();
+    ctx.set_parameter(&parameter_bytes);
+    let mut logger = TestLogger::init();
+
+    // Listings are per seller, so nobody else has one to update.
+    ctx.set_sender(Address::Account(CIS2_OPERATOR));
+    assert_eq!(
+        cis2_market::contract_update_price(&ctx, &mut host, &mut logger),
+        Err(cis2_market::MarketError::ListingNotFound)
+    );
+    assert_eq!(
+        market_view(&host).listings[0].listing.price,
+        Amount::from_ccd(10)
+    );
+
+    ctx.set_sender(Address::Account(CIS2_HOLDER));
+    assert!(cis2_market::contract_update_price(&ctx, &mut host, &mut logger).is_ok());
+    let view = market_view(&host);
+    assert_eq!(view.listings[0].listing.price, Amount::from_ccd(15));
+    assert_eq!(view.listings[0].listing.quantity, TokenAmountU64(3));
+    assert_eq!(
+        logger.logs,
+        [to_bytes(&cis2_market::MarketEvent::PriceUpdated {
+            key: view.listings[0].key.clone(),
+            price: Amount::from_ccd(15),
+        })]
+    );
+}
+
+// Verifier function for the cis2_market delist entrypoint
+#[concordium_test]
+fn verify_cis2_market_delist() {
+    let mut host = market_host();
+    market_list(&mut host, 3);
+    let key = market_view(&host).listings[0].key.clone();
+
+    let params = cis2_market::DelistParams {
+        contract: MARKET_TOKEN_CONTRACT,
+        token_id: TokenIdVec(vec![1]),
+    };
+    let parameter_bytes = to_bytes(&params);
+    let mut ctx = TestReceiveContext::empty();
+    ctx.set_parameter(&parameter_bytes);
+    let mut logger = TestLogger::init();
+
+    // Only the seller can take a listing down.
+    ctx.set_sender(Address::Account(CIS2_OPERATOR));
+    assert_eq!(
+        cis2_market::contract_delist(&ctx, &mut host, &mut logger),
+        Err(cis2_market::MarketError::ListingNotFound)
+    );
+    assert_eq!(market_view(&host).listings.len(), 1);
+
+    ctx.set_sender(Address::Account(CIS2_HOLDER));
+    assert!(cis2_market::contract_delist(&ctx, &mut host, &mut logger).is_ok());
+    assert!(market_view(&host).listings.is_empty());
+    assert_eq!(
+        logger.logs,
+        [to_bytes(&cis2_market::MarketEvent::Delisted { key })]
+    );
+
+    // A delisted token can no longer be bought, or delisted again.
+    assert_eq!(
+        cis2_market::contract_delist(&ctx, &mut host, &mut logger),
+        Err(cis2_market::MarketError::ListingNotFound)
+    );
+    let parameter_bytes = to_bytes(&cis2_market::BuyParams {
+        contract: MARKET_TOKEN_CONTRACT,
+        token_id: TokenIdVec(vec![1]),
+        owner: CIS2_HOLDER,
+        quantity: TokenAmountU64(1),
+    });
+    let mut buy_ctx = TestReceiveContext::empty();
+    buy_ctx.set_sender(Address::Account(CIS2_OPERATOR));
+    buy_ctx.set_parameter(&parameter_bytes);
+    assert_eq!(
+        cis2_market::contract_buy(&buy_ctx, &mut host, Amount::from_ccd(10), &mut logger),
+        Err(cis2_market::MarketError::ListingNotFound)
+    );
+}